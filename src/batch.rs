@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+use std::future::Future;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use crate::YouTubeError;
+
+/// Most ids the API accepts in a single `id` parameter
+pub(crate) const MAX_IDS_PER_REQUEST: usize = 50;
+
+/// Chunks sent at the same time when a request has more than `MAX_IDS_PER_REQUEST` ids
+pub(crate) const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Calls `send_chunk` with the comma-separated ids of every chunk, running up to `concurrency` of
+/// them at a time, and returns their results in chunk order. Fails with the first error.
+///
+/// Ids that appear more than once are only sent once, callers map the results back to every
+/// occurrence.
+pub(crate) async fn send_chunked<R, F, Fut>(ids: &[String], concurrency: usize, send_chunk: F) -> Result<Vec<R>, YouTubeError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<R, YouTubeError>>,
+{
+    let mut seen = HashSet::new();
+    let unique_ids: Vec<&str> = ids.iter().map(String::as_str).filter(|id| seen.insert(*id)).collect();
+
+    // Join up front, a closure over borrowed chunks would make the future not `Send`
+    let chunks: Vec<String> = unique_ids.chunks(MAX_IDS_PER_REQUEST).map(|chunk| chunk.join(",")).collect();
+
+    stream::iter(chunks.into_iter().map(send_chunk))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await
+}
@@ -1,36 +1,34 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::response::check_response;
+use http_body_util::Full;
 use hyper::body::Bytes;
-use http_body_util::{BodyExt, Full, Empty};
-use hyper::{Method, Request, StatusCode};
+use hyper::{Method, Request};
 use serde::{Deserialize, Serialize};
 
-pub struct DeleteChannelSectionRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct DeleteChannelSectionRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub id: String,
 }
 
-pub struct CreateChannelSectionRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct CreateChannelSectionRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub author_channel_id: String,
     pub position: u32,
     pub channels: Vec<String>,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for DeleteChannelSectionRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for DeleteChannelSectionRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for CreateChannelSectionRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for CreateChannelSectionRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
@@ -59,93 +57,38 @@ struct CreateChannelSectionResponse {
     id: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
-
-impl<'a> DeleteChannelSectionRequest<'a> {
+impl<T: Transport> DeleteChannelSectionRequest<T> {
     pub async fn send(self) -> Result<bool, YouTubeError> {
-        let url = format!("https://{}/youtube/v3/channelSections?id={}", self.ip, self.id);
-
-        let mut request_builder = Request::builder()
-            .method(Method::DELETE)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com");
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
+        let url = format!("https://{}/youtube/v3/channelSections?id={}", self.client.ip, self.id);
 
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
+        self.client.execute(ApiMethod::ChannelSectionsDelete, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::DELETE)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com");
 
-        if let Some(referrer) = self.fields.referrer {
-            request_builder = request_builder.header("Referer", referrer);
-        }
-
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
 
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            _ => {
-                                eprintln!("Unknown forbidden error message: {}", error_response.error.message);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        let body_str = String::from_utf8_lossy(&body_bytes);
-                        eprintln!("Failed to parse error response: {}\nResponse body: {}", e, body_str);
-                        return Err(YouTubeError::Forbidden)
-                    }
-                }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unauthorized error response: {}", body_str);
-                return Err(YouTubeError::Unauthorized);
-            },
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                return Err(YouTubeError::InternalServerError);
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-            StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError);
+
+            if let Some(referrer) = &self.fields.referrer {
+                request_builder = request_builder.header("Referer", referrer);
             }
-            StatusCode::NO_CONTENT => return Ok(true), // Success case for DELETE
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, |resp| check_response(resp).map(|_| true)).await
     }
 }
 
-impl<'a> CreateChannelSectionRequest<'a> {
+impl<T: Transport> CreateChannelSectionRequest<T> {
     pub async fn send(self) -> Result<String, YouTubeError> {
-        let url = format!("https://{}/youtube/v3/channelSections?part=snippet,id,contentDetails", self.ip);
+        let url = format!("https://{}/youtube/v3/channelSections?part=snippet,id,contentDetails", self.client.ip);
 
         let request_body = CreateChannelSectionRequestBody {
             snippet: ChannelSectionSnippet {
@@ -157,81 +100,36 @@ impl<'a> CreateChannelSectionRequest<'a> {
             },
         };
 
-        let body_json = serde_json::to_string(&request_body)
+        let body_json = serde_json::to_vec(&request_body)
+            .map(Bytes::from)
             .map_err(|e| YouTubeError::Other(Box::new(e)))?;
 
-        let mut request_builder = Request::builder()
-            .method(Method::POST)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header("Content-Type", "application/json");
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
-
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
+        let body_bytes = self.client.execute(ApiMethod::ChannelSectionsInsert, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::POST)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header("Content-Type", "application/json");
 
-        if let Some(referrer) = self.fields.referrer {
-            request_builder = request_builder.header("Referer", referrer);
-        }
-
-        let req = request_builder
-            .body(body_json.into())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
 
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            _ => {
-                                eprintln!("Unknown forbidden error message: {}", error_response.error.message);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        let body_str = String::from_utf8_lossy(&body_bytes);
-                        eprintln!("Failed to parse error response: {}\nResponse body: {}", e, body_str);
-                        return Err(YouTubeError::Forbidden)
-                    }
-                }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unauthorized error response: {}", body_str);
-                return Err(YouTubeError::Unauthorized);
-            },
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                return Err(YouTubeError::InternalServerError);
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-            StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError);
+
+            if let Some(referrer) = &self.fields.referrer {
+                request_builder = request_builder.header("Referer", referrer);
             }
-            StatusCode::OK => (), // Continue processing
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
 
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
+            request_builder
+                .body(Full::new(body_json.clone()))
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
+
         let api_response: CreateChannelSectionResponse = serde_json::from_slice(&body_bytes)?;
 
         Ok(api_response.id)
     }
-}
\ No newline at end of file
+}
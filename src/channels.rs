@@ -1,23 +1,25 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::collections::HashMap;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::response::check_response;
+use crate::batch::send_chunked;
+use http_body_util::Full;
+use hyper::{Method, Request};
 use chrono::DateTime;
 use serde::Deserialize;
 use crate::models::*;
 
-pub struct ListChannelsRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct ListChannelsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub channel_ids: Vec<String>,
+    /// Chunks of 50 ids sent at the same time
+    pub concurrency: usize,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for ListChannelsRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for ListChannelsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
@@ -104,98 +106,24 @@ struct ChannelImage {
     banner_external_url: Option<String>
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
-
-impl<'a> ListChannelsRequest<'a> {
+impl<T: Transport> ListChannelsRequest<T> {
+    /// Sets how many chunks of 50 ids are requested at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
 
+    /// Fetches the channels in chunks of 50 ids. Channels are returned in the order of
+    /// `channel_ids`, with a channel for every occurrence of a repeated id; ids that weren't
+    /// found are left out. Repeated ids are only requested once.
     pub async fn send(self) -> Result<Vec<Channel>, YouTubeError> {
 
-        let url = format!("https://{}/youtube/v3/channels?part=brandingSettings,id,snippet,statistics,status,localizations,topicDetails&id={}", self.ip, self.channel_ids.join(","));
-
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header("X-Goog-Fieldmask", "items(id,snippet(title,description,customUrl,publishedAt,country,thumbnails.default.url),statistics(subscriberCount,viewCount,videoCount),topicDetails.topicIds,brandingSettings(channel(keywords,unsubscribedTrailer,trackingAnalyticsAccountId),image.bannerExternalUrl),status.madeForKids)");
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
-
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
-
-        if let Some(referrer) = self.fields.referrer {
-            request_builder = request_builder.header("Referer", referrer);
-        }
-
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            _ => {
-                                eprintln!("Unknown forbidden error message: {}", error_response.error.message);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        let body_str = String::from_utf8_lossy(&body_bytes);
-                        eprintln!("Failed to parse error response: {}\nResponse body: {}", e, body_str);
-                        return Err(YouTubeError::Forbidden)
-                    }
-                }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unauthorized error response: {}", body_str);
-                return Err(YouTubeError::Unauthorized);
-            },
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                return Err(YouTubeError::InternalServerError);
-            }
-            StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError);
-            }
-            StatusCode::OK => (), // Continue processing
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
-        let api_response: ApiResponse = serde_json::from_slice(&body_bytes)?;
+        let responses = send_chunked(&self.channel_ids, self.concurrency, |ids_str| self.send_chunk(ids_str)).await?;
 
         // parse the response to Vec<Channel>
-        let channels: Vec<Channel> = api_response.items
-            .unwrap_or_default()
+        let channels: HashMap<String, Channel> = responses
             .into_iter()
+            .flat_map(|api_response| api_response.items.unwrap_or_default())
             .map(|channel| {
                 let profile_picture = channel.snippet
                     .as_ref()
@@ -288,10 +216,46 @@ impl<'a> ListChannelsRequest<'a> {
                         .and_then(|c| c.tracking_analytics_account_id.clone()),
                 }
             })
+            .map(|channel| (channel.user_id.clone(), channel))
+            .collect();
+
+        let channels = self.channel_ids
+            .iter()
+            .filter_map(|channel_id| channels.get(channel_id).cloned())
             .collect();
 
         Ok(channels)
     
     }
 
-}
\ No newline at end of file
+    async fn send_chunk(&self, ids_str: String) -> Result<ApiResponse, YouTubeError> {
+        let url = format!("https://{}/youtube/v3/channels?part=brandingSettings,id,snippet,statistics,status,localizations,topicDetails&id={}", self.client.ip, ids_str);
+
+        let body_bytes = self.client.execute(ApiMethod::ChannelsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header("X-Goog-Fieldmask", "items(id,snippet(title,description,customUrl,publishedAt,country,thumbnails.default.url),statistics(subscriberCount,viewCount,videoCount),topicDetails.topicIds,brandingSettings(channel(keywords,unsubscribedTrailer,trackingAnalyticsAccountId),image.bannerExternalUrl),status.madeForKids)");
+
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
+
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
+            }
+
+            if let Some(referrer) = &self.fields.referrer {
+                request_builder = request_builder.header("Referer", referrer);
+            }
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
+
+        Ok(serde_json::from_slice(&body_bytes)?)
+    }
+
+}
@@ -0,0 +1,67 @@
+use std::future::Future;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::YouTubeError;
+
+/// Progress of a paginated walk, see `PagedRequest::into_pages_with_checkpoint`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Token of the next page to fetch, `None` before the first page and after the last one
+    pub page_token: Option<String>,
+    /// Pages fetched so far
+    pub pages: u64,
+    /// Items fetched so far
+    pub items: u64,
+    /// Unix timestamp of the newest item seen so far
+    pub last_seen: Option<i64>,
+    /// Whether the last page has been fetched
+    pub finished: bool,
+}
+
+/// Where checkpoints are persisted. Implement this to keep them in a database.
+pub trait CheckpointStore: Send + Sync + 'static {
+    fn load(&self, key: &str) -> impl Future<Output = Result<Option<Checkpoint>, YouTubeError>> + Send;
+
+    fn save(&self, key: &str, checkpoint: &Checkpoint) -> impl Future<Output = Result<(), YouTubeError>> + Send;
+}
+
+/// Stores each checkpoint as a JSON file in a directory, named after the percent-encoded key
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    /// The directory is created on the first save
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileCheckpointStore { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Percent-encoding keeps file names safe without mapping different keys to the same file
+        self.dir.join(format!("{}.json", urlencoding::encode(key)))
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self, key: &str) -> Result<Option<Checkpoint>, YouTubeError> {
+        match tokio::fs::read(self.path(key)).await {
+            Ok(json) => serde_json::from_slice(&json).map(Some).map_err(|e| YouTubeError::Other(Box::new(e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(YouTubeError::Other(Box::new(e))),
+        }
+    }
+
+    async fn save(&self, key: &str, checkpoint: &Checkpoint) -> Result<(), YouTubeError> {
+        let json = serde_json::to_vec_pretty(checkpoint).map_err(|e| YouTubeError::Other(Box::new(e)))?;
+        let path = self.path(key);
+
+        // Write to a temporary file first so a crash never leaves a truncated checkpoint behind
+        let tmp_path = path.with_extension("json.tmp");
+        async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(&tmp_path, json).await?;
+            tokio::fs::rename(&tmp_path, &path).await
+        }.await.map_err(|e| YouTubeError::Other(Box::new(e)))
+    }
+}
@@ -1,28 +1,74 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::future::Future;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::pagination::PagedRequest;
+use crate::comments::{map_comments_disabled, TextFormat};
+use crate::response::{check_response, check_response_with};
+use crate::batch::send_chunked;
+use http_body_util::Full;
+use hyper::{Method, Request};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use crate::ids::{compact_channel_id, compact_comment_id, expand_comment_id};
 use crate::models::Comment;
+use std::collections::HashMap;
 
-pub struct GetCommentRepliesRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct GetCommentRepliesRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub parent_id: String,
-    pub page_token: Option<String>
+    pub page_token: Option<String>,
+    pub text_format: TextFormat,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for GetCommentRepliesRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for GetCommentRepliesRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
+impl<T> Clone for GetCommentRepliesRequest<T> {
+    fn clone(&self) -> Self {
+        GetCommentRepliesRequest {
+            client: self.client.clone(),
+            fields: self.fields.clone(),
+            parent_id: self.parent_id.clone(),
+            page_token: self.page_token.clone(),
+            text_format: self.text_format,
+        }
+    }
+}
+
+impl<T: Transport> PagedRequest for GetCommentRepliesRequest<T> {
+    type Page = GetCommentRepliesResponse;
+    type Item = Comment;
+
+    fn send_page(self) -> impl Future<Output = Result<Self::Page, YouTubeError>> + Send {
+        self.send()
+    }
+
+    fn set_page_token(&mut self, page_token: String) {
+        self.page_token = Some(page_token);
+    }
+
+    fn next_page_token(page: &Self::Page) -> Option<&str> {
+        page.next_page_token.as_deref()
+    }
+
+    fn items(page: &Self::Page) -> &[Self::Item] {
+        &page.comments
+    }
+
+    fn into_items(page: Self::Page) -> Vec<Self::Item> {
+        page.comments
+    }
+
+    fn item_timestamp(item: &Self::Item) -> Option<i64> {
+        Some(item.published_at)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
     #[serde(rename = "nextPageToken")]
@@ -38,7 +84,10 @@ struct CommentItem {
 
 #[derive(Debug, Deserialize)]
 struct CommentSnippet {
-    #[serde(rename = "textOriginal")]
+    // Only requested when looking up comments by id
+    #[serde(rename = "videoId")]
+    video_id: Option<String>,
+    #[serde(rename = "textOriginal", alias = "textDisplay")]
     text_original: String,
     #[serde(rename = "authorChannelId")]
     author_channel_id: AuthorChannelId,
@@ -55,99 +104,59 @@ struct AuthorChannelId {
     value: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
-
 #[derive(Debug)]
 pub struct GetCommentRepliesResponse {
     pub next_page_token: Option<String>,
     pub comments: Vec<Comment>,
 }
 
-impl<'a> GetCommentRepliesRequest<'a> {
+impl<T: Transport> GetCommentRepliesRequest<T> {
+    /// With `TextFormat::Html`, `Comment::text` is the displayed HTML instead of the original text.
+    pub fn with_text_format(mut self, text_format: TextFormat) -> Self {
+        self.text_format = text_format;
+        self
+    }
+
     pub async fn send(self) -> Result<GetCommentRepliesResponse, YouTubeError> {
+        let (text_format, text_field) = self.text_format.param_and_field();
         let mut url = format!(
-            "https://{}/youtube/v3/comments?part=snippet,id&maxResults=100&parentId={}&textFormat=plainText",
-            self.ip, self.parent_id
+            "https://{}/youtube/v3/comments?part=snippet,id&maxResults=100&parentId={}&textFormat={}",
+            self.client.ip, expand_comment_id(&self.parent_id), text_format
         );
         
         if let Some(page_token) = self.page_token {
             url = format!("{}&pageToken={}", url, page_token);
         }
 
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header(
-                "X-Goog-Fieldmask", 
-                "nextPageToken,items(id,snippet(textOriginal,authorChannelId.value,likeCount,publishedAt,updatedAt))"
-            );
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
+        let body_bytes = self.client.execute(ApiMethod::CommentsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header(
+                    "X-Goog-Fieldmask", 
+                    format!("nextPageToken,items(id,snippet({text_field},authorChannelId.value,likeCount,publishedAt,updatedAt))")
+                );
 
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
 
-        if let Some(referrer) = self.fields.referrer {
-            if !referrer.is_empty() {
-                request_builder = request_builder.header("Referer", referrer);
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-        }
 
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.contains("parameter has disabled comments") => {
-                                return Err(YouTubeError::NotFound)
-                            },
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            msg => {
-                                eprintln!("Unknown forbidden error message: {}", msg);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(_) => return Err(YouTubeError::Forbidden),
+            if let Some(referrer) = &self.fields.referrer {
+                if !referrer.is_empty() {
+                    request_builder = request_builder.header("Referer", referrer);
                 }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => return Err(YouTubeError::Unauthorized),
-            StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError)
-            },
-            StatusCode::OK => (),
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
+            }
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, |resp| check_response_with(resp, map_comments_disabled)).await?;
+
         let api_response: ApiResponse = serde_json::from_slice(&body_bytes)?;
 
         let mut comments = Vec::new();
@@ -157,7 +166,8 @@ impl<'a> GetCommentRepliesRequest<'a> {
             let idx = match item.id.rfind('.') {
                 Some(idx) => idx,
                 None => {
-                    println!("Skipping comment with invalid ID format: {}", item.id);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(comment_id = %item.id, "skipping reply with invalid id format");
                     continue;
                 }
             };
@@ -166,21 +176,16 @@ impl<'a> GetCommentRepliesRequest<'a> {
             let reply_id = &item.id[idx+1..];
 
             // Clean up the parent ID
-            let parent_id = parent_id
-                .strip_prefix("Ug")
-                .and_then(|s| s.strip_suffix("4AaABAg"))
-                .unwrap_or(parent_id);
+            let parent_id = compact_comment_id(parent_id);
 
             if item.snippet.author_channel_id.value.is_empty() {
-                println!("(empty user id) reply_id: {}, user_id: {}", reply_id, item.snippet.author_channel_id.value);
+                #[cfg(feature = "tracing")]
+                tracing::debug!(reply_id, "skipping reply with empty author channel id");
                 continue
             }
 
             let comment = Comment {
-                user_id: item.snippet.author_channel_id.value
-                    .strip_prefix("UC")
-                    .unwrap_or(&item.snippet.author_channel_id.value)
-                    .to_string(),
+                user_id: compact_channel_id(&item.snippet.author_channel_id.value).to_string(),
                 comment_id: reply_id.to_string(),
                 parent_user_id: None,
                 parent_comment_id: Some(parent_id.to_string()),
@@ -203,4 +208,120 @@ impl<'a> GetCommentRepliesRequest<'a> {
             comments,
         })
     }
-}
\ No newline at end of file
+}
+
+pub struct ListCommentsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
+    pub comment_ids: Vec<String>,
+    /// Chunks of 50 ids sent at the same time
+    pub concurrency: usize,
+}
+
+impl<T> AsMut<GoogleAPIRequestFields> for ListCommentsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
+        &mut self.fields
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListCommentsResponse {
+    #[serde(default)]
+    items: Vec<CommentItem>,
+}
+
+impl<T: Transport> ListCommentsRequest<T> {
+    /// Sets how many chunks of 50 ids are requested at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Fetches the comments in chunks of 50 ids. Comments are returned in the order of
+    /// `comment_ids`, with a comment for every occurrence of a repeated id; ids that weren't found
+    /// are left out. An id given both compact and full counts as repeated and is requested once.
+    ///
+    /// The API doesn't return reply counts or the author of the parent comment, so `reply_count`
+    /// is 0 and `parent_user_id` is `None`.
+    pub async fn send(self) -> Result<Vec<Comment>, YouTubeError> {
+        let full_ids: Vec<String> = self.comment_ids.iter().map(|id| expand_comment_id(id)).collect();
+
+        let responses = send_chunked(&full_ids, self.concurrency, |ids| self.send_chunk(ids)).await?;
+
+        let mut comments: HashMap<String, Comment> = HashMap::new();
+
+        for item in responses.into_iter().flat_map(|response| response.items) {
+            if item.snippet.author_channel_id.value.is_empty() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(comment_id = %item.id, "skipping comment with empty author channel id");
+                continue
+            }
+
+            let (comment_id, parent_comment_id) = match item.id.split_once('.') {
+                Some((parent_id, reply_id)) => (reply_id.to_string(), Some(compact_comment_id(parent_id).to_string())),
+                None => (compact_comment_id(&item.id).to_string(), None),
+            };
+
+            let comment = Comment {
+                user_id: compact_channel_id(&item.snippet.author_channel_id.value).to_string(),
+                comment_id,
+                parent_user_id: None,
+                parent_comment_id,
+                video_id: item.snippet.video_id,
+                text: item.snippet.text_original,
+                like_count: item.snippet.like_count,
+                reply_count: 0,
+                published_at: item.snippet.published_at.timestamp(),
+                updated_at: if item.snippet.updated_at == item.snippet.published_at {
+                    None
+                } else {
+                    Some(item.snippet.updated_at.timestamp())
+                },
+            };
+            comments.insert(item.id, comment);
+        }
+
+        Ok(full_ids
+            .iter()
+            .filter_map(|id| comments.get(id).cloned())
+            .collect())
+    }
+
+    async fn send_chunk(&self, comment_ids: String) -> Result<ListCommentsResponse, YouTubeError> {
+        let url = format!(
+            "https://{}/youtube/v3/comments?part=snippet,id&textFormat=plainText&id={}",
+            self.client.ip, comment_ids
+        );
+
+        let body_bytes = self.client.execute(ApiMethod::CommentsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header(
+                    "X-Goog-Fieldmask",
+                    "items(id,snippet(videoId,textOriginal,authorChannelId.value,likeCount,publishedAt,updatedAt))"
+                );
+
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
+
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
+            }
+
+            if let Some(referrer) = &self.fields.referrer {
+                if !referrer.is_empty() {
+                    request_builder = request_builder.header("Referer", referrer);
+                }
+            }
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
+
+        Ok(serde_json::from_slice(&body_bytes)?)
+    }
+}
@@ -1,29 +1,117 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::future::Future;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::pagination::PagedRequest;
+use crate::response::{check_response, check_response_with, ApiError, ErrorConstructor};
+use crate::batch::send_chunked;
+use http_body_util::Full;
+use hyper::{Method, Request};
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use crate::models::Comment;
+use serde::{Deserialize, Serialize};
+use crate::ids::{compact_channel_id, compact_comment_id, expand_comment_id};
+use crate::models::{Comment, CommentOwner};
 use std::collections::HashMap;
 
-pub struct GetCommentsRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
-    pub channel_id: String,
-    pub page_token: Option<String>
+/// Which comment threads `GetCommentsRequest` lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentThreadFilter {
+    /// Threads on a channel's videos, by channel id
+    Channel(String),
+    /// Threads on a single video, by video id
+    Video(String),
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for GetCommentsRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommentOrder {
+    /// Newest first
+    #[default]
+    Time,
+    Relevance,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextFormat {
+    #[default]
+    PlainText,
+    /// The text as displayed on YouTube, with links and formatting as HTML
+    Html,
+}
+
+impl TextFormat {
+    /// The `textFormat` parameter and the snippet field holding the text in that format
+    pub(crate) fn param_and_field(self) -> (&'static str, &'static str) {
+        match self {
+            TextFormat::PlainText => ("plainText", "textOriginal"),
+            TextFormat::Html => ("html", "textDisplay"),
+        }
+    }
+}
+
+pub struct GetCommentsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
+    pub filter: CommentThreadFilter,
+    pub page_token: Option<String>,
+    pub order: CommentOrder,
+    /// Only return threads containing these terms
+    pub search_terms: Option<String>,
+    pub text_format: TextFormat,
+    /// Fetch every reply of threads with more replies than the API embeds
+    pub expand_replies: bool,
+}
+
+impl<T> AsMut<GoogleAPIRequestFields> for GetCommentsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
+impl<T> Clone for GetCommentsRequest<T> {
+    fn clone(&self) -> Self {
+        GetCommentsRequest {
+            client: self.client.clone(),
+            fields: self.fields.clone(),
+            filter: self.filter.clone(),
+            page_token: self.page_token.clone(),
+            order: self.order,
+            search_terms: self.search_terms.clone(),
+            text_format: self.text_format,
+            expand_replies: self.expand_replies,
+        }
+    }
+}
+
+impl<T: Transport> PagedRequest for GetCommentsRequest<T> {
+    type Page = GetCommentsResponse;
+    type Item = Comment;
+
+    fn send_page(self) -> impl Future<Output = Result<Self::Page, YouTubeError>> + Send {
+        self.send()
+    }
+
+    fn set_page_token(&mut self, page_token: String) {
+        self.page_token = Some(page_token);
+    }
+
+    fn next_page_token(page: &Self::Page) -> Option<&str> {
+        page.next_page_token.as_deref()
+    }
+
+    fn items(page: &Self::Page) -> &[Self::Item] {
+        &page.comments
+    }
+
+    fn into_items(page: Self::Page) -> Vec<Self::Item> {
+        page.comments
+    }
+
+    fn item_timestamp(item: &Self::Item) -> Option<i64> {
+        Some(item.published_at)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
     #[serde(rename = "nextPageToken")]
@@ -66,7 +154,8 @@ struct ReplyComment {
 struct CommentSnippet {
     #[serde(rename = "videoId")]
     video_id: Option<String>,
-    #[serde(rename = "textOriginal")]
+    // Only one of them is requested, depending on the text format
+    #[serde(rename = "textOriginal", alias = "textDisplay")]
     text_original: String,
     #[serde(rename = "authorChannelId")]
     author_channel_id: AuthorChannelId,
@@ -83,120 +172,107 @@ struct AuthorChannelId {
     value: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
-
 #[derive(Debug)]
 pub struct GetCommentsResponse {
     pub next_page_token: Option<String>,
     pub comments: Vec<Comment>,
 }
 
-impl<'a> GetCommentsRequest<'a> {
+impl<T: Transport> GetCommentsRequest<T> {
+    /// The API embeds at most a few replies per thread. With `expand_replies`, the remaining ones
+    /// are fetched with `GetCommentRepliesRequest`, one extra request per page of replies.
+    pub fn with_expand_replies(mut self, expand_replies: bool) -> Self {
+        self.expand_replies = expand_replies;
+        self
+    }
+
+    pub fn with_order(mut self, order: CommentOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_search_terms(mut self, search_terms: impl Into<String>) -> Self {
+        self.search_terms = Some(search_terms.into());
+        self
+    }
+
+    /// With `TextFormat::Html`, `Comment::text` is the displayed HTML instead of the original text.
+    pub fn with_text_format(mut self, text_format: TextFormat) -> Self {
+        self.text_format = text_format;
+        self
+    }
+
     pub async fn send(self) -> Result<GetCommentsResponse, YouTubeError> {
+        let filter = match &self.filter {
+            CommentThreadFilter::Channel(channel_id) => format!("allThreadsRelatedToChannelId={}", channel_id),
+            CommentThreadFilter::Video(video_id) => format!("videoId={}", video_id),
+        };
+        let (text_format, text_field) = self.text_format.param_and_field();
+        let order = match self.order {
+            CommentOrder::Time => "time",
+            CommentOrder::Relevance => "relevance",
+        };
+
         let mut url = format!(
-            "https://{}/youtube/v3/commentThreads?maxResults=100&allThreadsRelatedToChannelId={}&textFormat=plainText&order=time&part=snippet,replies",
-            self.ip, self.channel_id
+            "https://{}/youtube/v3/commentThreads?maxResults=100&{}&textFormat={}&order={}&part=snippet,replies",
+            self.client.ip, filter, text_format, order
         );
+
+        if let Some(search_terms) = &self.search_terms {
+            url = format!("{}&searchTerms={}", url, urlencoding::encode(search_terms));
+        }
         
-        if let Some(page_token) = self.page_token {
+        if let Some(page_token) = &self.page_token {
             url = format!("{}&pageToken={}", url, page_token);
         }
 
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header(
-                "X-Goog-Fieldmask", 
-                "nextPageToken,items(snippet(topLevelComment(id,snippet(videoId,textOriginal,authorChannelId.value,likeCount,publishedAt,updatedAt)),totalReplyCount),replies.comments(id,snippet(textOriginal,authorChannelId.value,likeCount,publishedAt,updatedAt)))"
-            );
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
-
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
+        let body_bytes = self.client.execute(ApiMethod::CommentThreadsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header(
+                    "X-Goog-Fieldmask", 
+                    format!("nextPageToken,items(snippet(topLevelComment(id,snippet(videoId,{text_field},authorChannelId.value,likeCount,publishedAt,updatedAt)),totalReplyCount),replies.comments(id,snippet({text_field},authorChannelId.value,likeCount,publishedAt,updatedAt)))")
+                );
+
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
 
-        if let Some(referrer) = self.fields.referrer {
-            if !referrer.is_empty() {
-                request_builder = request_builder.header("Referer", referrer);
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-        }
 
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.contains("parameter has disabled comments") => {
-                                return Err(YouTubeError::NotFound)
-                            },
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            msg => {
-                                eprintln!("Unknown forbidden error message: {}", msg);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(_) => return Err(YouTubeError::Forbidden),
+            if let Some(referrer) = &self.fields.referrer {
+                if !referrer.is_empty() {
+                    request_builder = request_builder.header("Referer", referrer);
                 }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => return Err(YouTubeError::Unauthorized),
-            StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError)
-            },
-            StatusCode::OK => (),
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
+            }
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, |resp| check_response_with(resp, map_comments_disabled)).await?;
 
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
         let api_response: ApiResponse = serde_json::from_slice(&body_bytes)?;
 
         let mut comments = Vec::new();
 
         for item in api_response.items {
-            let user_id = item.snippet.top_level_comment.snippet.author_channel_id.value
-                .strip_prefix("UC")
-                .unwrap_or(&item.snippet.top_level_comment.snippet.author_channel_id.value)
-                .to_string();
+            let user_id = compact_channel_id(&item.snippet.top_level_comment.snippet.author_channel_id.value).to_string();
 
-            let comment_id = item.snippet.top_level_comment.id
-                .strip_prefix("Ug")
-                .and_then(|s| s.strip_suffix("4AaABAg"))
-                .unwrap_or(&item.snippet.top_level_comment.id)
-                .to_string();
+            let comment_id = compact_comment_id(&item.snippet.top_level_comment.id).to_string();
 
             if user_id.is_empty() {
-                println!("(empty user id) comment_id: {}, user_id: {}, video_id: {}", comment_id, user_id, item.snippet.top_level_comment.snippet.video_id.clone().unwrap_or_default());
+                #[cfg(feature = "tracing")]
+                tracing::debug!(comment_id, video_id = ?item.snippet.top_level_comment.snippet.video_id, "skipping comment with empty author channel id");
                 continue
             }
 
+            let thread_id = item.snippet.top_level_comment.id.clone();
+            let parent_user_id = user_id.clone();
+
             // Add top-level comment
             let thread_comment = Comment {
                 user_id,
@@ -216,47 +292,54 @@ impl<'a> GetCommentsRequest<'a> {
             };
             comments.push(thread_comment);
 
-            if let Some(replies) = item.replies {
-                for reply in replies.comments {
-                    let (mut parent_id, reply_id) = reply.id.split_once('.')
-                        .unwrap_or((&reply.id, ""));
-
-                    parent_id = parent_id
-                        .strip_prefix("Ug")
-                        .and_then(|s| s.strip_suffix("4AaABAg"))
-                        .unwrap_or(&parent_id);
+            let embedded_replies = item.replies.map(|replies| replies.comments).unwrap_or_default();
 
-                    if reply.snippet.author_channel_id.value.is_empty() {
-                        println!("(empty user id) reply_id: {}, user_id: {}, video_id: {}", reply_id, reply.snippet.author_channel_id.value, item.snippet.top_level_comment.snippet.video_id.clone().unwrap_or_default());
+            if self.expand_replies && (embedded_replies.len() as i32) < item.snippet.total_reply_count {
+                match self.fetch_replies(thread_id).await {
+                    Ok(replies) => {
+                        for mut reply in replies {
+                            // The replies endpoint doesn't know the thread
+                            reply.parent_user_id = Some(parent_user_id.clone());
+                            reply.video_id = item.snippet.top_level_comment.snippet.video_id.clone();
+                            comments.push(reply);
+                        }
                         continue
-                    }
+                    },
+                    // The thread was deleted in the meantime, keep the embedded replies
+                    Err(YouTubeError::NotFound(_)) => {},
+                    Err(e) => return Err(e),
+                }
+            }
+
+            for reply in embedded_replies {
+                let (mut parent_id, reply_id) = reply.id.split_once('.')
+                    .unwrap_or((&reply.id, ""));
 
-                    let reply_comment = Comment {
-                        user_id: reply.snippet.author_channel_id.value
-                            .strip_prefix("UC")
-                            .unwrap_or(&reply.snippet.author_channel_id.value)
-                            .to_string(),
-                        comment_id: reply_id.to_string(),
-                        parent_user_id: Some(
-                            item.snippet.top_level_comment.snippet.author_channel_id.value
-                                .strip_prefix("UC")
-                                .unwrap_or(&item.snippet.top_level_comment.snippet.author_channel_id.value)
-                                .to_string()
-                        ),
-                        parent_comment_id: Some(parent_id.to_string()),
-                        video_id: item.snippet.top_level_comment.snippet.video_id.clone(),
-                        text: reply.snippet.text_original,
-                        like_count: reply.snippet.like_count,
-                        reply_count: 0,  // Replies can't have replies
-                        published_at: reply.snippet.published_at.timestamp(),
-                        updated_at: if reply.snippet.updated_at == reply.snippet.published_at {
-                            None
-                        } else {
-                            Some(reply.snippet.updated_at.timestamp())
-                        },
-                    };
-                    comments.push(reply_comment);
+                parent_id = compact_comment_id(parent_id);
+
+                if reply.snippet.author_channel_id.value.is_empty() {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(reply_id, video_id = ?item.snippet.top_level_comment.snippet.video_id, "skipping reply with empty author channel id");
+                    continue
                 }
+
+                let reply_comment = Comment {
+                    user_id: compact_channel_id(&reply.snippet.author_channel_id.value).to_string(),
+                    comment_id: reply_id.to_string(),
+                    parent_user_id: Some(parent_user_id.clone()),
+                    parent_comment_id: Some(parent_id.to_string()),
+                    video_id: item.snippet.top_level_comment.snippet.video_id.clone(),
+                    text: reply.snippet.text_original,
+                    like_count: reply.snippet.like_count,
+                    reply_count: 0,  // Replies can't have replies
+                    published_at: reply.snippet.published_at.timestamp(),
+                    updated_at: if reply.snippet.updated_at == reply.snippet.published_at {
+                        None
+                    } else {
+                        Some(reply.snippet.updated_at.timestamp())
+                    },
+                };
+                comments.push(reply_comment);
             }
         }
 
@@ -265,24 +348,122 @@ impl<'a> GetCommentsRequest<'a> {
             comments,
         })
     }
+
+    /// Every reply of a thread, sent with the same key and headers as this request
+    async fn fetch_replies(&self, thread_id: String) -> Result<Vec<Comment>, YouTubeError> {
+        let mut request = self.client.get_comment_replies(thread_id, None);
+        request.fields = self.fields.clone();
+        request.text_format = self.text_format;
+        request.stream().try_collect().await
+    }
+}
+
+/// The newest top-level comment seen by a sync, see `GetCommentsRequest::sync`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentWatermark {
+    pub comment_id: String,
+    pub published_at: i64,
+}
+
+/// A page of comments that are new since the watermark
+#[derive(Debug)]
+pub struct CommentSyncPage {
+    pub comments: Vec<Comment>,
+    /// The watermark to store for the next sync, only set on the last page so it isn't saved
+    /// before the older pages are processed
+    pub watermark: Option<CommentWatermark>,
+}
+
+struct SyncState<R> {
+    request: Option<R>,
+    since: Option<CommentWatermark>,
+    newest: Option<CommentWatermark>,
+}
+
+impl<T: Transport> GetCommentsRequest<T> {
+    /// Fetches the comments posted since `since`, newest first.
+    ///
+    /// Pagination stops at the page holding the watermark comment or the first older top-level
+    /// comment. Replies are included if they are newer than the watermark and belong to a thread
+    /// on one of the fetched pages; new replies to older threads are missed. With no watermark,
+    /// every comment is fetched. The order is always `CommentOrder::Time`.
+    pub fn sync(mut self, since: Option<CommentWatermark>) -> BoxStream<'static, Result<CommentSyncPage, YouTubeError>> {
+        // Stopping at the watermark only works newest first
+        self.order = CommentOrder::Time;
+
+        let state = SyncState {
+            request: Some(self),
+            since,
+            newest: None,
+        };
+
+        stream::try_unfold(state, |mut state| async move {
+            let Some(request) = state.request.take() else {
+                return Ok(None);
+            };
+
+            let mut next_request = request.clone();
+            let page = request.send().await?;
+
+            let mut reached_watermark = false;
+            let mut comments = Vec::with_capacity(page.comments.len());
+            for comment in page.comments {
+                let is_new = match &state.since {
+                    None => true,
+                    Some(since) if comment.parent_comment_id.is_none() => {
+                        // Threads are ordered newest first, so everything after the watermark is old
+                        if reached_watermark || comment.comment_id == since.comment_id || comment.published_at < since.published_at {
+                            reached_watermark = true;
+                        }
+                        !reached_watermark
+                    },
+                    Some(since) => comment.published_at > since.published_at,
+                };
+
+                if comment.parent_comment_id.is_none() && is_new && state.newest.is_none() {
+                    state.newest = Some(CommentWatermark {
+                        comment_id: comment.comment_id.clone(),
+                        published_at: comment.published_at,
+                    });
+                }
+
+                if is_new {
+                    comments.push(comment);
+                }
+            }
+
+            let watermark = match page.next_page_token {
+                Some(page_token) if !page_token.is_empty() && !reached_watermark => {
+                    next_request.page_token = Some(page_token);
+                    state.request = Some(next_request);
+                    None
+                },
+                _ => state.newest.clone().or_else(|| state.since.clone()),
+            };
+
+            Ok(Some((CommentSyncPage { comments, watermark }, state)))
+        }).boxed()
+    }
 }
 
 // New structs for getting video IDs by comment IDs
-pub struct GetCommentVideoIdsRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
-    pub comment_ids: Vec<String>
+pub struct GetCommentVideoIdsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
+    pub comment_ids: Vec<String>,
+    /// Chunks of 50 ids sent at the same time
+    pub concurrency: usize,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for GetCommentVideoIdsRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for GetCommentVideoIdsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct CommentVideoIdsResponse {
+    #[serde(default)]
     items: Vec<CommentVideoIdItem>,
 }
 
@@ -313,97 +494,107 @@ struct TopLevelCommentSnippet {
 
 #[derive(Debug)]
 pub struct GetCommentVideoIdsResponse {
-    pub comment_id_to_video_user: HashMap<String, String>,
+    /// Owner of every comment that was found, keyed by comment id
+    pub owners: HashMap<String, CommentOwner>,
+    /// Requested comment ids that weren't found, in request order
+    pub not_found: Vec<String>,
 }
 
-impl<'a> GetCommentVideoIdsRequest<'a> {
+impl<T: Transport> GetCommentVideoIdsRequest<T> {
+    /// Sets how many chunks of 50 ids are requested at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Resolves the comments in chunks of 50 ids. Takes full ids and the compact ids in `Comment`,
+    /// and reports them back as given, under every form a comment was requested in. Only
+    /// top-level comments can be resolved, replies end up in `not_found`.
     pub async fn send(self) -> Result<GetCommentVideoIdsResponse, YouTubeError> {
-        // Join comment IDs with commas
-        let comment_ids = self.comment_ids.join(",");
-        
+        // Compact ids are sent expanded, and reported back as they were requested
+        let full_ids: Vec<String> = self.comment_ids.iter().map(|comment_id| expand_comment_id(comment_id)).collect();
+        let mut requested_ids: HashMap<&String, Vec<&String>> = HashMap::new();
+        for (full_id, comment_id) in full_ids.iter().zip(&self.comment_ids) {
+            requested_ids.entry(full_id).or_default().push(comment_id);
+        }
+
+        let responses = send_chunked(&full_ids, self.concurrency, |comment_ids| self.send_chunk(comment_ids)).await?;
+
+        let mut owners = HashMap::new();
+
+        for item in responses.into_iter().flat_map(|api_response| api_response.items) {
+            let author_channel_id = item.snippet.top_level_comment.snippet.author_channel_id.value;
+            let author_user_id = compact_channel_id(&author_channel_id).to_string();
+            let owner = CommentOwner {
+                video_id: item.snippet.video_id,
+                author_user_id,
+            };
+
+            match requested_ids.get(&item.id) {
+                Some(comment_ids) => {
+                    for comment_id in comment_ids {
+                        owners.insert(comment_id.to_string(), owner.clone());
+                    }
+                },
+                None => {
+                    owners.insert(item.id, owner);
+                },
+            }
+        }
+
+        let not_found = self.comment_ids
+            .iter()
+            .filter(|comment_id| !owners.contains_key(*comment_id))
+            .cloned()
+            .collect();
+
+        Ok(GetCommentVideoIdsResponse {
+            owners,
+            not_found,
+        })
+    }
+
+    async fn send_chunk(&self, comment_ids: String) -> Result<CommentVideoIdsResponse, YouTubeError> {
         let url = format!(
             "https://{}/youtube/v3/commentThreads?part=snippet,id&id={}",
-            self.ip, comment_ids
+            self.client.ip, comment_ids
         );
 
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header("X-Goog-Fieldmask", "items(id,snippet(videoId,topLevelComment.snippet.authorChannelId.value))");
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
+        let body_bytes = self.client.execute(ApiMethod::CommentThreadsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header("X-Goog-Fieldmask", "items(id,snippet(videoId,topLevelComment.snippet.authorChannelId.value))");
 
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
 
-        if let Some(referrer) = self.fields.referrer {
-            if !referrer.is_empty() {
-                request_builder = request_builder.header("Referer", referrer);
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-        }
 
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            msg => {
-                                eprintln!("Unknown forbidden error message: {}", msg);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(_) => return Err(YouTubeError::Forbidden),
+            if let Some(referrer) = &self.fields.referrer {
+                if !referrer.is_empty() {
+                    request_builder = request_builder.header("Referer", referrer);
                 }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => return Err(YouTubeError::Unauthorized),
-            StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError)
-            },
-            StatusCode::OK => (),
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
-        let api_response: CommentVideoIdsResponse = serde_json::from_slice(&body_bytes)?;
+            }
 
-        let mut comment_id_to_video_user = HashMap::new();
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
 
-        for item in api_response.items {
-            let user_id = item.snippet.top_level_comment.snippet.author_channel_id.value
-                .strip_prefix("UC")
-                .unwrap_or(&item.snippet.top_level_comment.snippet.author_channel_id.value)
-                .to_string();
-            
-            // Create the combined value of video_id:user_id
-            let video_user = format!("{}:{}", item.snippet.video_id, user_id);
-            
-            comment_id_to_video_user.insert(item.id, video_user);
-        }
+        Ok(serde_json::from_slice(&body_bytes)?)
+    }
+}
 
-        Ok(GetCommentVideoIdsResponse {
-            comment_id_to_video_user,
-        })
+/// Comments on videos and channels that disabled them are reported as not found
+pub(crate) fn map_comments_disabled(error: &ApiError) -> Option<ErrorConstructor> {
+    if error.has_reason(&["commentsDisabled"]) || error.message.contains("parameter has disabled comments") {
+        return Some(YouTubeError::NotFound);
     }
-}
\ No newline at end of file
+
+    None
+}
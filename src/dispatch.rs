@@ -0,0 +1,207 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use hyper::body::Bytes;
+use hyper::{Request, Response, StatusCode};
+use http_body_util::Full;
+use crate::{GoogleAPIRequestFields, Transport, YouTubeDataV3Client, YouTubeError};
+use crate::metrics::RequestEvent;
+use crate::quota::ApiMethod;
+use crate::retry::parse_retry_after;
+use crate::transport::ConnectTimeout;
+
+impl<T: Transport> YouTubeDataV3Client<T> {
+    /// Sends the request produced by `build` and maps the response with `check`, retrying
+    /// according to the client's `RetryPolicy`. `build` is called again for every attempt with
+    /// the API key to authorize it with.
+    ///
+    /// Without a key in `fields`, keys are taken from the client's `KeyPool`. A key that runs out
+    /// of quota is marked exhausted and the request is sent again with the next one, which
+    /// doesn't count as a retry.
+    ///
+    /// The timeouts in `fields` take precedence over the client's.
+    pub(crate) async fn execute<B, C, R>(&self, method: ApiMethod, fields: &GoogleAPIRequestFields, build: B, check: C) -> Result<R, YouTubeError>
+    where
+        B: Fn(Option<&str>) -> Result<Request<Full<Bytes>>, YouTubeError>,
+        C: Fn(Response<Bytes>) -> Result<R, YouTubeError>,
+    {
+        let attempts = self.attempts(method, fields, build, check);
+        let timed = async {
+            match fields.timeout.or(self.timeout) {
+                Some(timeout) => tokio::time::timeout(timeout, attempts).await.map_err(|_| YouTubeError::Timeout)?,
+                None => attempts.await,
+            }
+        };
+
+        #[cfg(not(feature = "tracing"))]
+        return timed.await;
+
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+
+            let span = tracing::info_span!(
+                "youtube_request",
+                endpoint = method.name(),
+                quota_units = method.quota_cost(),
+                key_id = tracing::field::Empty,
+                status = tracing::field::Empty,
+                attempts = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            );
+            let started = std::time::Instant::now();
+            let result = timed.instrument(span.clone()).await;
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+            if let Err(e) = &result {
+                span.in_scope(|| tracing::warn!(error = %e, "request failed"));
+            }
+            result
+        }
+    }
+
+    async fn attempts<B, C, R>(&self, method: ApiMethod, fields: &GoogleAPIRequestFields, build: B, check: C) -> Result<R, YouTubeError>
+    where
+        B: Fn(Option<&str>) -> Result<Request<Full<Bytes>>, YouTubeError>,
+        C: Fn(Response<Bytes>) -> Result<R, YouTubeError>,
+    {
+        let key = fields.key.as_deref();
+        let attempt_timeout = fields.attempt_timeout.or(self.attempt_timeout);
+        let connect_timeout = fields.connect_timeout.or(self.connect_timeout);
+        let key_pool = self.key_pool.as_ref().filter(|_| key.is_none());
+        let mut key: Option<Arc<str>> = key.map(Arc::from);
+        let mut attempt = 0;
+
+        loop {
+            if let Some(key_pool) = key_pool {
+                if key.is_none() {
+                    key = Some(key_pool.next_key().ok_or(YouTubeError::QuotaExceeded(None))?);
+                }
+            }
+
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire(method).await;
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("key_id", key.as_deref().map(key_id));
+
+            let charged = match (&self.quota_tracker, key.as_deref()) {
+                (Some(quota_tracker), Some(key)) => quota_tracker.charge(key, method),
+                _ => Ok(()),
+            };
+
+            let (error, retry_after) = match charged {
+                Ok(()) => {
+                    let mut req = build(key.as_deref())?;
+                    if let Some(connect_timeout) = connect_timeout {
+                        req.extensions_mut().insert(ConnectTimeout(connect_timeout));
+                    }
+                    let url = req.uri().to_string();
+                    let started = Instant::now();
+
+                    match self.send(req, attempt_timeout).await {
+                        Ok(resp) => {
+                            let status = resp.status();
+
+                            #[cfg(feature = "tracing")]
+                            {
+                                tracing::Span::current().record("status", status.as_u16());
+                                tracing::debug!(status = status.as_u16(), latency_ms = started.elapsed().as_millis() as u64, "received response");
+                            }
+
+                            let retry_after = parse_retry_after(resp.headers());
+                            match check(resp) {
+                                Ok(result) => {
+                                    self.record_metrics(method, Some(status), None, started);
+                                    return Ok(result);
+                                },
+                                Err(mut e) => {
+                                    e.set_url(&url);
+                                    self.record_metrics(method, Some(status), Some(&e), started);
+                                    (e, retry_after)
+                                },
+                            }
+                        },
+                        Err(e) => {
+                            self.record_metrics(method, None, Some(&e), started);
+                            (e, None)
+                        },
+                    }
+                },
+                // Over budget, so not worth retrying with the same key
+                Err(e) => (e, None),
+            };
+
+            if let Some(key_pool) = key_pool {
+                if matches!(error, YouTubeError::QuotaExceeded(_) | YouTubeError::QuotaBudgetExceeded { .. }) {
+                    if let Some(key) = key.take() {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(key_id = key_id(&key), error = %error, "API key exhausted, failing over to the next one");
+                        key_pool.mark_exhausted(&key);
+                    }
+                    continue;
+                }
+            }
+
+            if matches!(error, YouTubeError::QuotaBudgetExceeded { .. }) {
+                return Err(error);
+            }
+
+            attempt += 1;
+
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("attempts", attempt);
+
+            // Sending a non-idempotent call again could apply it twice
+            if !method.is_idempotent() && outcome_unknown(&error) {
+                return Err(error);
+            }
+
+            match self.retry_policy.next_delay(attempt, &error, retry_after) {
+                Some(delay) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(error = %error, attempt, delay_ms = delay.as_millis() as u64, "retrying request");
+                    tokio::time::sleep(delay).await
+                },
+                None => return Err(error),
+            }
+        }
+    }
+
+    fn record_metrics(&self, method: ApiMethod, status: Option<StatusCode>, error: Option<&YouTubeError>, started: Instant) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_request(&RequestEvent {
+                method,
+                status,
+                error: error.map(YouTubeError::kind),
+                latency: started.elapsed(),
+                // Requests that got no response may never have reached the API
+                quota_units: if status.is_some() { method.quota_cost() } else { 0 },
+            });
+        }
+    }
+
+    async fn send(&self, req: Request<Full<Bytes>>, attempt_timeout: Option<Duration>) -> Result<Response<Bytes>, YouTubeError> {
+        match attempt_timeout {
+            Some(attempt_timeout) => tokio::time::timeout(attempt_timeout, self.transport.request(req)).await.map_err(|_| YouTubeError::Timeout)?,
+            None => self.transport.request(req).await,
+        }
+    }
+}
+
+/// Whether the API may have acted on a request that failed with `error`: the connection broke or
+/// timed out after the request was sent, or the server failed while handling it
+fn outcome_unknown(error: &YouTubeError) -> bool {
+    match error {
+        YouTubeError::LegacyHttpError(e) => !e.is_connect(),
+        YouTubeError::Timeout | YouTubeError::HttpError(_) | YouTubeError::InternalServerError(_) => true,
+        _ => false,
+    }
+}
+
+/// Identifies a key in logs without revealing it
+#[cfg(feature = "tracing")]
+fn key_id(key: &str) -> String {
+    let start = key.char_indices().rev().nth(3).map_or(0, |(i, _)| i);
+    format!("...{}", &key[start..])
+}
@@ -0,0 +1,262 @@
+//! Typed ids, converting between the ids the API uses and the compact forms stored in `Comment`
+//! and the other models.
+//!
+//! | Type         | Full                                 | Compact                          |
+//! |--------------|--------------------------------------|----------------------------------|
+//! | `VideoId`    | `dQw4w9WgXcQ`                        | same                             |
+//! | `ChannelId`  | `UCuAXFkgsw1L7xaCfnd5JJOw`           | `uAXFkgsw1L7xaCfnd5JJOw`         |
+//! | `CommentId`  | `UgwI3PTCpt1hMSmE8D54AaABAg`         | `wI3PTCpt1hMSmE8D5`              |
+//! | `ReplyId`    | `UgwI3PTCpt1hMSmE8D54AaABAg.9xyz`    | `wI3PTCpt1hMSmE8D5.9xyz`         |
+//! | `PlaylistId` | `PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf` | same                             |
+//!
+//! `parse` takes either form and `compact` converts back, so `parse(id.compact())` always gives
+//! `id` again. `Display` and `as_str` give the full form that requests expect. Comment ids from
+//! before the `Ug…` format, like `z13…`, have no compact form and are kept as they are.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::ClientError;
+
+const THREAD_PREFIX: &str = "Ug";
+const THREAD_SUFFIX: &str = "4AaABAg";
+
+/// Longer than any current comment id, full or compact
+const LEGACY_MIN_LEN: usize = 27;
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+fn validate(id: &str, len: Option<usize>) -> Result<(), ClientError> {
+    let valid = !id.is_empty()
+        && id.chars().all(is_id_char)
+        && len.is_none_or(|len| id.len() == len);
+
+    match valid {
+        true => Ok(()),
+        false => Err(ClientError::InvalidId(id.to_string())),
+    }
+}
+
+/// Whether `id` is an older, Google+ era comment id such as `z13fsdm4ykyyxbrud04cgxp5pyqpjzn4z2c`,
+/// which is only lowercase letters and digits and longer than current ids
+fn is_legacy_comment_id(id: &str) -> bool {
+    id.len() >= LEGACY_MIN_LEN
+        && id.starts_with('z')
+        && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Strips the `UC` prefix, as the models do for channel ids
+pub(crate) fn compact_channel_id(id: &str) -> &str {
+    id.strip_prefix("UC").unwrap_or(id)
+}
+
+/// Strips the `Ug` prefix and `4AaABAg` suffix of a top-level comment id, unless the rest would
+/// itself look like a full or legacy id and couldn't be told apart when expanding
+pub(crate) fn compact_comment_id(id: &str) -> &str {
+    match id.strip_prefix(THREAD_PREFIX).and_then(|s| s.strip_suffix(THREAD_SUFFIX)) {
+        Some(compact) if !compact.is_empty() && !compact.starts_with(THREAD_PREFIX) && !is_legacy_comment_id(compact) => compact,
+        _ => id,
+    }
+}
+
+/// Expands a top-level or `parent.reply` comment id in either form, leaving ids it doesn't
+/// recognize alone
+pub(crate) fn expand_comment_id(id: &str) -> String {
+    match (id.parse::<CommentId>(), id.parse::<ReplyId>()) {
+        (Ok(comment_id), _) => comment_id.0,
+        (_, Ok(reply_id)) => reply_id.id,
+        _ => id.to_string(),
+    }
+}
+
+macro_rules! impl_id {
+    ($name:ident) => {
+        impl $name {
+            /// The full id, as sent to the API
+            pub fn as_str(&self) -> &str {
+                self.as_ref()
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ClientError;
+
+            fn from_str(id: &str) -> Result<Self, Self::Err> {
+                $name::parse(id)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> String {
+                id.as_str().to_string()
+            }
+        }
+    };
+}
+
+/// An 11 character video id. The compact form is the same.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoId(String);
+
+impl VideoId {
+    pub fn parse(id: &str) -> Result<Self, ClientError> {
+        validate(id, Some(11))?;
+        Ok(VideoId(id.to_string()))
+    }
+
+    pub fn compact(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for VideoId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl_id!(VideoId);
+
+/// A channel id, `UC` followed by 22 characters. The compact form leaves out `UC`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelId(String);
+
+impl ChannelId {
+    pub fn parse(id: &str) -> Result<Self, ClientError> {
+        // The compact form may start with UC too, so tell them apart by length
+        let full = match id.len() {
+            24 if id.starts_with("UC") => id.to_string(),
+            22 => format!("UC{}", id),
+            _ => return Err(ClientError::InvalidId(id.to_string())),
+        };
+        validate(&full, Some(24))?;
+        Ok(ChannelId(full))
+    }
+
+    pub fn compact(&self) -> &str {
+        compact_channel_id(&self.0)
+    }
+
+    /// The playlist of the channel's uploads
+    pub fn uploads_playlist(&self) -> PlaylistId {
+        PlaylistId(format!("UU{}", self.compact()))
+    }
+}
+
+impl AsRef<str> for ChannelId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl_id!(ChannelId);
+
+/// The id of a top-level comment, which is also the id of its thread.
+///
+/// Current ids look like `Ug…4AaABAg` and are compacted by leaving out the prefix and suffix.
+/// Older ids, `Ug…` without the suffix or Google+ era `z13…` ids, are kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommentId(String);
+
+impl CommentId {
+    pub fn parse(id: &str) -> Result<Self, ClientError> {
+        validate(id, None)?;
+        match id.starts_with(THREAD_PREFIX) || is_legacy_comment_id(id) {
+            true => Ok(CommentId(id.to_string())),
+            false => Ok(CommentId(format!("{}{}{}", THREAD_PREFIX, id, THREAD_SUFFIX))),
+        }
+    }
+
+    pub fn compact(&self) -> &str {
+        compact_comment_id(&self.0)
+    }
+}
+
+impl AsRef<str> for CommentId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl_id!(CommentId);
+
+/// The id of a reply, `parent.reply` where `parent` is the `CommentId` of the thread.
+///
+/// `Comment::comment_id` only holds the part after the dot, use `from_parts` with
+/// `Comment::parent_comment_id` to get the whole id back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReplyId {
+    id: String,
+    // Position of the dot
+    split: usize,
+}
+
+impl ReplyId {
+    pub fn parse(id: &str) -> Result<Self, ClientError> {
+        let (parent, reply) = id.split_once('.').ok_or_else(|| ClientError::InvalidId(id.to_string()))?;
+        ReplyId::from_parts(&CommentId::parse(parent)?, reply)
+    }
+
+    pub fn from_parts(parent: &CommentId, reply: &str) -> Result<Self, ClientError> {
+        validate(reply, None)?;
+        Ok(ReplyId {
+            id: format!("{}.{}", parent.as_str(), reply),
+            split: parent.as_str().len(),
+        })
+    }
+
+    /// The thread this reply belongs to
+    pub fn parent(&self) -> CommentId {
+        CommentId(self.id[..self.split].to_string())
+    }
+
+    /// The part after the dot, as stored in `Comment::comment_id`
+    pub fn reply(&self) -> &str {
+        &self.id[self.split + 1..]
+    }
+
+    pub fn compact(&self) -> String {
+        format!("{}.{}", compact_comment_id(&self.id[..self.split]), self.reply())
+    }
+}
+
+impl AsRef<str> for ReplyId {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl_id!(ReplyId);
+
+/// A playlist id such as `PL…` or `UU…`. The compact form is the same.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlaylistId(String);
+
+impl PlaylistId {
+    pub fn parse(id: &str) -> Result<Self, ClientError> {
+        validate(id, None)?;
+        if id.len() < 3 {
+            return Err(ClientError::InvalidId(id.to_string()));
+        }
+        Ok(PlaylistId(id.to_string()))
+    }
+
+    pub fn compact(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for PlaylistId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl_id!(PlaylistId);
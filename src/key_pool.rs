@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use chrono::{NaiveDate, Utc};
+use rand::Rng;
+use crate::quota::pacific_date;
+
+/// How `KeyPool` picks the key for the next request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySelection {
+    /// Keys take turns in the order they were added
+    RoundRobin,
+    /// Keys are picked at random in proportion to their weight
+    Weighted,
+}
+
+/// A set of API keys that requests are spread over.
+///
+/// When the API reports that a key's quota is exceeded, the key is marked exhausted and the
+/// request is sent again with the next healthy key. Exhausted keys become healthy again at
+/// midnight Pacific time, when the API quota resets.
+///
+/// A key set on the request with `GoogleAPIRequest::with_key` always takes precedence over the
+/// pool.
+#[derive(Debug)]
+pub struct KeyPool {
+    keys: Vec<(Arc<str>, u32)>,
+    selection: KeySelection,
+    next: AtomicUsize,
+    exhausted: Mutex<Vec<Option<NaiveDate>>>,
+}
+
+impl KeyPool {
+    /// Creates a pool that uses `keys` in turn
+    pub fn new(keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        KeyPool::from_keys(keys.into_iter().map(|key| (key, 1)), KeySelection::RoundRobin)
+    }
+
+    /// Creates a pool that picks each key with a probability proportional to its weight.
+    /// Keys with a weight of 0 are never used.
+    pub fn weighted(keys: impl IntoIterator<Item = (impl Into<String>, u32)>) -> Self {
+        KeyPool::from_keys(keys, KeySelection::Weighted)
+    }
+
+    fn from_keys(keys: impl IntoIterator<Item = (impl Into<String>, u32)>, selection: KeySelection) -> Self {
+        let keys: Vec<(Arc<str>, u32)> = keys.into_iter()
+            .map(|(key, weight)| (Arc::from(key.into()), weight))
+            .collect();
+
+        KeyPool {
+            exhausted: Mutex::new(vec![None; keys.len()]),
+            keys,
+            selection,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn selection(&self) -> KeySelection {
+        self.selection
+    }
+
+    /// Marks `key` as exhausted until the quota resets
+    pub fn mark_exhausted(&self, key: &str) {
+        let today = pacific_date(Utc::now());
+        let mut exhausted = self.exhausted.lock().unwrap();
+        for (i, (pooled_key, _)) in self.keys.iter().enumerate() {
+            if &**pooled_key == key {
+                exhausted[i] = Some(today);
+            }
+        }
+    }
+
+    /// Keys that haven't run out of quota today
+    pub fn healthy_keys(&self) -> Vec<String> {
+        let healthy = self.healthy();
+        self.keys.iter()
+            .zip(healthy)
+            .filter(|(_, healthy)| *healthy)
+            .map(|((key, _), _)| key.to_string())
+            .collect()
+    }
+
+    /// Whether every key has run out of quota today, or the healthy ones all have a weight of 0
+    pub fn is_drained(&self) -> bool {
+        !self.keys.iter()
+            .zip(self.healthy())
+            .any(|((_, weight), healthy)| healthy && *weight > 0)
+    }
+
+    /// Picks the key for the next request, or `None` if the pool is drained
+    pub(crate) fn next_key(&self) -> Option<Arc<str>> {
+        let healthy = self.healthy();
+        let usable = |i: usize| healthy[i] && self.keys[i].1 > 0;
+
+        match self.selection {
+            KeySelection::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..self.keys.len())
+                    .map(|offset| (start + offset) % self.keys.len())
+                    .find(|i| usable(*i))
+                    .map(|i| self.keys[i].0.clone())
+            },
+            KeySelection::Weighted => {
+                let total: u64 = (0..self.keys.len()).filter(|i| usable(*i)).map(|i| self.keys[i].1 as u64).sum();
+                if total == 0 {
+                    return None;
+                }
+
+                let mut pick = rand::thread_rng().gen_range(0..total);
+                for i in (0..self.keys.len()).filter(|i| usable(*i)) {
+                    let weight = self.keys[i].1 as u64;
+                    if pick < weight {
+                        return Some(self.keys[i].0.clone());
+                    }
+                    pick -= weight;
+                }
+                None
+            },
+        }
+    }
+
+    fn healthy(&self) -> Vec<bool> {
+        let today = pacific_date(Utc::now());
+        self.exhausted.lock().unwrap()
+            .iter()
+            .map(|exhausted_on| *exhausted_on != Some(today))
+            .collect()
+    }
+}
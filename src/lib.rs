@@ -1,27 +1,53 @@
 use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 use hyper::body::Bytes;
-use hyper_tls::HttpsConnector;
 use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use http_body_util::{Empty, Full};
+use http_body_util::Full;
 use hyper::StatusCode;
 use thiserror::Error;
-use native_tls::TlsConnector;
-use hyper_util::rt::TokioExecutor;
 
 #[cfg(test)]
 mod tests;
 
 pub mod models;
+pub mod ids;
+pub use ids::{ChannelId, CommentId, PlaylistId, ReplyId, VideoId};
 pub mod utils;
+pub mod transport;
+pub use transport::{ConnectTimeout, Transport, HyperTransport};
+pub mod connector;
+pub use connector::{AddressRotation, ClientBuilder, Connector};
+pub mod proxy;
+pub use proxy::{Proxy, ProxyScheme};
+pub mod retry;
+pub use retry::RetryPolicy;
+pub mod quota;
+pub use quota::{ApiMethod, QuotaTracker};
+pub mod key_pool;
+pub use key_pool::{KeyPool, KeySelection};
+pub mod rate_limit;
+pub use rate_limit::RateLimiter;
+pub mod metrics;
+pub use metrics::{MetricsRecorder, PrometheusMetrics, RequestEvent};
+pub mod pagination;
+pub use pagination::PagedRequest;
+pub mod page_token;
+pub use page_token::PageToken;
+pub mod checkpoint;
+pub use checkpoint::{Checkpoint, CheckpointStore, FileCheckpointStore};
+mod batch;
+mod dispatch;
+mod response;
 pub mod videos;
 pub use videos::ListVideosRequest;
 pub mod channels;
 pub use channels::ListChannelsRequest;
 pub mod comments;
-pub use comments::{GetCommentsRequest, GetCommentsResponse, GetCommentVideoIdsRequest, GetCommentVideoIdsResponse};
+pub use comments::{CommentOrder, CommentSyncPage, CommentThreadFilter, CommentWatermark, GetCommentsRequest, GetCommentsResponse, GetCommentVideoIdsRequest, GetCommentVideoIdsResponse, TextFormat};
 pub mod comment_replies;
-pub use comment_replies::{GetCommentRepliesRequest, GetCommentRepliesResponse};
+pub use comment_replies::{GetCommentRepliesRequest, GetCommentRepliesResponse, ListCommentsRequest};
 pub mod subscriptions;
 pub use subscriptions::{GetSubscriptionsRequest, GetSubscriptionsResponse};
 pub mod channel_section;
@@ -36,132 +62,160 @@ pub enum ClientError {
     #[error("TLS error: {0}")]
     TlsError(#[from] native_tls::Error),
     #[error("Invalid IP: {0}")]
-    InvalidIp(String)
+    InvalidIp(String),
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+    #[error("Invalid id: {0}")]
+    InvalidId(String)
 }
 
-pub fn initialize_client() -> Result<Client<HttpsConnector<HttpConnector>, Empty<Bytes>>, ClientError> {
-    let mut http = HttpConnector::new();
-    http.enforce_http(false);
-    
-    //if let Some(subnet) = subnet {
-    //    let random_ip = crate::utils::get_rand_ipv6(subnet, subnet_id.unwrap_or_default()).map_err(|e| ClientError::InvalidIp(e.to_string()))?;
-    //    http.set_local_address(Some(random_ip));
-    //}
-    
-    let tls = TlsConnector::builder()
-        .danger_accept_invalid_certs(true)
-        .build()?;
-
-    // Create an HTTPS connector using the HTTP connector and the custom TLS connector
-    let https = HttpsConnector::from((http, tls.into()));
-    
-    // Create the client with the custom service
-    let client = Client::builder(TokioExecutor::new())
-        .build::<_, Empty<Bytes>>(https);
-
-    Ok(client)
+/// The hyper client used by `HyperTransport`. Requests carry a `Full<Bytes>` body, which is
+/// simply left empty for GET and DELETE requests.
+pub type HttpClient = Client<Connector, Full<Bytes>>;
+
+/// Creates an `HttpClient` with the default `ClientBuilder` settings, which verify certificates
+pub fn initialize_client() -> Result<HttpClient, ClientError> {
+    ClientBuilder::new().build()
 }
 
-pub fn initialize_full_client() -> Result<Client<HttpsConnector<HttpConnector>, Full<Bytes>>, ClientError> {
-    let mut http = HttpConnector::new();
-    http.enforce_http(false);
-    
-    //if let Some(subnet) = subnet {
-    //    let random_ip = crate::utils::get_rand_ipv6(subnet, subnet_id.unwrap_or_default()).map_err(|e| ClientError::InvalidIp(e.to_string()))?;
-    //    http.set_local_address(Some(random_ip));
-    //}
-    
-    let tls = TlsConnector::builder()
-        .danger_accept_invalid_certs(true)
-        .build()?;
-
-    // Create an HTTPS connector using the HTTP connector and the custom TLS connector
-    let https = HttpsConnector::from((http, tls.into()));
-    
-    // Create the client with the custom service
-    let client = Client::builder(TokioExecutor::new())
-        .build::<_, Full<Bytes>>(https);
-
-    Ok(client)
+#[deprecated(note = "`initialize_client` now returns a client that supports request bodies")]
+pub fn initialize_full_client() -> Result<HttpClient, ClientError> {
+    initialize_client()
 }
 
-pub struct GoogleAPIRequestFields<'a> {
-    pub bearer_token: Option<&'a str>,
-    pub key: Option<&'a str>,
-    pub referrer: Option<&'a str>,
+#[derive(Default, Clone)]
+pub struct GoogleAPIRequestFields {
+    pub bearer_token: Option<String>,
+    pub key: Option<String>,
+    pub referrer: Option<String>,
+    pub timeout: Option<Duration>,
+    pub attempt_timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
 }
 
-pub trait GoogleAPIRequest<'a> {
-    fn bearer_token(&mut self) -> &mut Option<&'a str>;
+pub trait GoogleAPIRequest {
+    fn bearer_token(&mut self) -> &mut Option<String>;
+
+    fn key(&mut self) -> &mut Option<String>;
+
+    fn referrer(&mut self) -> &mut Option<String>;
+
+    fn timeout(&mut self) -> &mut Option<Duration>;
 
-    fn key(&mut self) -> &mut Option<&'a str>;
+    fn attempt_timeout(&mut self) -> &mut Option<Duration>;
 
-    fn referrer(&mut self) -> &mut Option<&'a str>;
+    fn connect_timeout(&mut self) -> &mut Option<Duration>;
+
+    fn with_bearer_token(mut self, bearer_token: impl Into<String>) -> Self
+    where
+        Self: Sized,
+    {
+        *self.bearer_token() = Some(bearer_token.into());
+        self
+    }
 
-    fn with_bearer_token(mut self, bearer_token: &'a str) -> Self
+    fn with_key(mut self, key: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        *self.bearer_token() = Some(bearer_token);
+        *self.key() = Some(key.into());
         self
     }
 
-    fn with_key(mut self, key: &'a str) -> Self
+    fn with_referrer(mut self, referrer: impl Into<String>) -> Self
     where
         Self: Sized,
     {
-        *self.key() = Some(key);
+        *self.referrer() = Some(referrer.into());
         self
     }
 
-    fn with_referrer(mut self, referrer: &'a str) -> Self
+    /// Overrides the client's total timeout for this request, see `YouTubeDataV3Client::with_timeout`
+    fn with_timeout(mut self, timeout: Duration) -> Self
     where
         Self: Sized,
     {
-        *self.referrer() = Some(referrer);
+        *self.timeout() = Some(timeout);
+        self
+    }
+
+    /// Overrides the client's attempt timeout for this request, see `YouTubeDataV3Client::with_attempt_timeout`
+    fn with_attempt_timeout(mut self, attempt_timeout: Duration) -> Self
+    where
+        Self: Sized,
+    {
+        *self.attempt_timeout() = Some(attempt_timeout);
+        self
+    }
+
+    /// Overrides the client's connect timeout for this request, see `YouTubeDataV3Client::with_connect_timeout`
+    fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self
+    where
+        Self: Sized,
+    {
+        *self.connect_timeout() = Some(connect_timeout);
         self
     }
 }
 
-impl<'a, T> GoogleAPIRequest<'a> for T
+impl<T> GoogleAPIRequest for T
 where
-    T: AsMut<GoogleAPIRequestFields<'a>>,
+    T: AsMut<GoogleAPIRequestFields>,
 {
-    fn bearer_token(&mut self) -> &mut Option<&'a str> {
+    fn bearer_token(&mut self) -> &mut Option<String> {
         &mut self.as_mut().bearer_token
     }
 
-    fn key(&mut self) -> &mut Option<&'a str> {
+    fn key(&mut self) -> &mut Option<String> {
         &mut self.as_mut().key
     }
 
-    fn referrer(&mut self) -> &mut Option<&'a str> {
+    fn referrer(&mut self) -> &mut Option<String> {
         &mut self.as_mut().referrer
     }
+
+    fn timeout(&mut self) -> &mut Option<Duration> {
+        &mut self.as_mut().timeout
+    }
+
+    fn attempt_timeout(&mut self) -> &mut Option<Duration> {
+        &mut self.as_mut().attempt_timeout
+    }
+
+    fn connect_timeout(&mut self) -> &mut Option<Duration> {
+        &mut self.as_mut().connect_timeout
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum YouTubeError {
-    #[error("Not found")]
-    NotFound,
-    #[error("Ratelimited")]
-    Ratelimited,
-    #[error("Unauthorized")]
-    Unauthorized,
-    #[error("Forbidden")]
-    Forbidden,
-    #[error("Internal server error")]
-    InternalServerError,
-    #[error("Unknown Status Code")]
-    UnknownStatusCode(StatusCode),
-    #[error("Account Closed")]
-    AccountClosed,
-    #[error("Account Terminated")]
-    AccountTerminated,
-    #[error("Subscriptions Private")] // Subscriptions specific
-    SubscriptionsPrivate,
+    #[error("Not found: {0}")]
+    NotFound(Box<ApiErrorDetails>),
+    #[error("Ratelimited: {0}")]
+    Ratelimited(Box<ApiErrorDetails>),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(Box<ApiErrorDetails>),
+    #[error("Forbidden: {0}")]
+    Forbidden(Box<ApiErrorDetails>),
+    #[error("Internal server error: {0}")]
+    InternalServerError(Box<ApiErrorDetails>),
+    #[error("Unknown status code: {0}")]
+    UnknownStatusCode(Box<ApiErrorDetails>),
+    #[error("Account Closed: {0}")]
+    AccountClosed(Box<ApiErrorDetails>),
+    #[error("Account Terminated: {0}")]
+    AccountTerminated(Box<ApiErrorDetails>),
+    #[error("Subscriptions Private: {0}")] // Subscriptions specific
+    SubscriptionsPrivate(Box<ApiErrorDetails>),
     #[error("Parse error")]
     ParseError(String),
+    #[error("Request timed out")]
+    Timeout,
+    /// `None` if every key in the `KeyPool` was already exhausted, so no request was sent
+    #[error("Quota exceeded")]
+    QuotaExceeded(Option<Box<ApiErrorDetails>>),
+    #[error("Quota budget exceeded: {used} of {budget} units used, request costs {cost}")]
+    QuotaBudgetExceeded { used: u64, cost: u64, budget: u64 },
     #[error("HTTP error: {0}")]
     HttpError(#[from] hyper::Error),
     #[error("Legacy HTTP error: {0}")]
@@ -172,122 +226,351 @@ pub enum YouTubeError {
     Other(Box<dyn Error + Send + Sync>),
 }
 
-pub struct YouTubeDataV3Client {
-    client: Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    ip: String
+impl YouTubeError {
+    /// Whether the request might succeed if sent again: server errors, rate limiting, timeouts
+    /// and transport errors
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            YouTubeError::InternalServerError(_)
+                | YouTubeError::Ratelimited(_)
+                | YouTubeError::Timeout
+                | YouTubeError::HttpError(_)
+                | YouTubeError::LegacyHttpError(_)
+        )
+    }
+
+    /// A short snake_case name for the variant, e.g. `quota_exceeded`, for use in metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            YouTubeError::NotFound(_) => "not_found",
+            YouTubeError::Ratelimited(_) => "ratelimited",
+            YouTubeError::Unauthorized(_) => "unauthorized",
+            YouTubeError::Forbidden(_) => "forbidden",
+            YouTubeError::InternalServerError(_) => "internal_server_error",
+            YouTubeError::UnknownStatusCode(_) => "unknown_status_code",
+            YouTubeError::AccountClosed(_) => "account_closed",
+            YouTubeError::AccountTerminated(_) => "account_terminated",
+            YouTubeError::SubscriptionsPrivate(_) => "subscriptions_private",
+            YouTubeError::ParseError(_) => "parse_error",
+            YouTubeError::Timeout => "timeout",
+            YouTubeError::QuotaExceeded(_) => "quota_exceeded",
+            YouTubeError::QuotaBudgetExceeded { .. } => "quota_budget_exceeded",
+            YouTubeError::HttpError(_) => "http_error",
+            YouTubeError::LegacyHttpError(_) => "legacy_http_error",
+            YouTubeError::ProtobufError(_) => "protobuf_error",
+            YouTubeError::Other(_) => "other",
+        }
+    }
+
+    /// Whether the API key is out of quota, either according to the API or to a `QuotaTracker`
+    pub fn is_quota(&self) -> bool {
+        matches!(self, YouTubeError::QuotaExceeded(_) | YouTubeError::QuotaBudgetExceeded { .. })
+    }
+
+    /// What the API said about the error, for every error response
+    pub fn details(&self) -> Option<&ApiErrorDetails> {
+        match self {
+            YouTubeError::NotFound(details)
+            | YouTubeError::Ratelimited(details)
+            | YouTubeError::Unauthorized(details)
+            | YouTubeError::Forbidden(details)
+            | YouTubeError::InternalServerError(details)
+            | YouTubeError::UnknownStatusCode(details)
+            | YouTubeError::AccountClosed(details)
+            | YouTubeError::AccountTerminated(details)
+            | YouTubeError::SubscriptionsPrivate(details)
+            | YouTubeError::QuotaExceeded(Some(details)) => Some(details),
+            _ => None,
+        }
+    }
+
+    fn details_mut(&mut self) -> Option<&mut ApiErrorDetails> {
+        match self {
+            YouTubeError::NotFound(details)
+            | YouTubeError::Ratelimited(details)
+            | YouTubeError::Unauthorized(details)
+            | YouTubeError::Forbidden(details)
+            | YouTubeError::InternalServerError(details)
+            | YouTubeError::UnknownStatusCode(details)
+            | YouTubeError::AccountClosed(details)
+            | YouTubeError::AccountTerminated(details)
+            | YouTubeError::SubscriptionsPrivate(details)
+            | YouTubeError::QuotaExceeded(Some(details)) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// The Google error reason, e.g. `forbidden` or `videoNotFound`
+    pub fn reason(&self) -> Option<&str> {
+        self.details()?.reason.as_deref()
+    }
+
+    pub(crate) fn set_url(&mut self, url: &str) {
+        if let Some(details) = self.details_mut() {
+            details.url = Some(url.to_string());
+        }
+    }
+}
+
+/// An error response from the API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiErrorDetails {
+    pub status: StatusCode,
+    /// The first `errors[].reason` in the error body, e.g. `quotaExceeded` or `commentsDisabled`
+    pub reason: Option<String>,
+    /// The first `errors[].domain` in the error body, e.g. `youtube.quota`
+    pub domain: Option<String>,
+    /// The error message, or the raw body if it wasn't a Google error envelope
+    pub message: String,
+    /// The URL of the failed request
+    pub url: Option<String>,
+}
+
+impl fmt::Display for ApiErrorDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status.as_u16())?;
+        if let Some(reason) = &self.reason {
+            write!(f, " {}", reason)?;
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " ({})", url)?;
+        }
+        Ok(())
+    }
+}
+
+/// Client for the YouTube Data API v3.
+///
+/// Cloning is cheap and all clones share the same transport, so one client can be used to
+/// issue many requests concurrently, e.g. from `tokio::spawn`ed tasks.
+pub struct YouTubeDataV3Client<T = HyperTransport> {
+    pub(crate) transport: Arc<T>,
+    pub(crate) ip: Arc<str>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) quota_tracker: Option<Arc<QuotaTracker>>,
+    pub(crate) key_pool: Option<Arc<KeyPool>>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) attempt_timeout: Option<Duration>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) metrics: Option<Arc<dyn MetricsRecorder>>
+}
+
+impl<T> Clone for YouTubeDataV3Client<T> {
+    fn clone(&self) -> Self {
+        YouTubeDataV3Client {
+            transport: self.transport.clone(),
+            ip: self.ip.clone(),
+            retry_policy: self.retry_policy.clone(),
+            quota_tracker: self.quota_tracker.clone(),
+            key_pool: self.key_pool.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            timeout: self.timeout,
+            attempt_timeout: self.attempt_timeout,
+            connect_timeout: self.connect_timeout,
+            metrics: self.metrics.clone()
+        }
+    }
 }
 
 impl YouTubeDataV3Client {
-    pub async fn new(ip: String, client: Client<HttpsConnector<HttpConnector>, Empty<Bytes>>) -> Self {
+    pub async fn new(ip: String, client: HttpClient) -> Self {
+        YouTubeDataV3Client::from_transport(ip, HyperTransport::new(client))
+    }
+}
+
+impl<T: Transport> YouTubeDataV3Client<T> {
+    /// Creates a client that sends every request through a custom `Transport`
+    pub fn from_transport(ip: String, transport: T) -> Self {
         YouTubeDataV3Client {
-            client,
-            ip
+            transport: Arc::new(transport),
+            ip: ip.into(),
+            retry_policy: RetryPolicy::disabled(),
+            quota_tracker: None,
+            key_pool: None,
+            rate_limiter: None,
+            timeout: None,
+            attempt_timeout: None,
+            connect_timeout: None,
+            metrics: None
         }
     }
 
-    pub fn get_subscriptions<'a>(&'a mut self, channel_id: String, page_token: Option<String>) -> GetSubscriptionsRequest<'a> {
+    /// Retries failed requests according to `retry_policy`. Requests are only sent once by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Counts the quota units spent per API key. The tracker can be shared between clients.
+    pub fn with_quota_tracker(mut self, quota_tracker: Arc<QuotaTracker>) -> Self {
+        self.quota_tracker = Some(quota_tracker);
+        self
+    }
+
+    pub fn quota_tracker(&self) -> Option<&Arc<QuotaTracker>> {
+        self.quota_tracker.as_ref()
+    }
+
+    /// Authorizes requests that have no key of their own with keys from `key_pool`, failing
+    /// over to the next key when one runs out of quota
+    pub fn with_key_pool(mut self, key_pool: Arc<KeyPool>) -> Self {
+        self.key_pool = Some(key_pool);
+        self
+    }
+
+    pub fn key_pool(&self) -> Option<&Arc<KeyPool>> {
+        self.key_pool.as_ref()
+    }
+
+    /// Makes `send()` wait for a permit from `rate_limiter` before each request. The limiter can
+    /// be shared between clients to apply one limit to all of them.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Limits how long `send()` may take in total, including retries and waiting for the rate
+    /// limiter. There is no limit by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits how long each attempt may take in total, from connecting (if no idle connection is
+    /// reused) to receiving the whole body. A timed out attempt is retried like a connection
+    /// error. There is no limit by default.
+    pub fn with_attempt_timeout(mut self, attempt_timeout: Duration) -> Self {
+        self.attempt_timeout = Some(attempt_timeout);
+        self
+    }
+
+    /// Limits how long establishing a connection may take, including the TLS handshake. Takes
+    /// precedence over `ClientBuilder::with_connect_timeout`.
+    ///
+    /// The limit is passed to the transport as a `ConnectTimeout` request extension. Custom
+    /// transports that don't look for it ignore it.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Reports every attempt to `metrics`, e.g. a shared `PrometheusMetrics`
+    pub fn with_metrics_recorder(mut self, metrics: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn get_subscriptions(&self, channel_id: String, page_token: Option<String>) -> GetSubscriptionsRequest<T> {
         GetSubscriptionsRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
             channel_id,
             page_token
         }
     }
 
-    pub fn get_comments<'a>(&'a mut self, channel_id: String, page_token: Option<String>) -> GetCommentsRequest<'a> {
+    /// Comment threads on all videos of a channel
+    pub fn get_comments(&self, channel_id: String, page_token: Option<String>) -> GetCommentsRequest<T> {
+        self.comment_threads(CommentThreadFilter::Channel(channel_id), page_token)
+    }
+
+    /// Comment threads on a single video
+    pub fn get_video_comments(&self, video_id: String, page_token: Option<String>) -> GetCommentsRequest<T> {
+        self.comment_threads(CommentThreadFilter::Video(video_id), page_token)
+    }
+
+    fn comment_threads(&self, filter: CommentThreadFilter, page_token: Option<String>) -> GetCommentsRequest<T> {
         GetCommentsRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
-            channel_id,
-            page_token
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            filter,
+            page_token,
+            order: CommentOrder::default(),
+            search_terms: None,
+            text_format: TextFormat::default(),
+            expand_replies: false,
         }
     }
 
-    pub fn get_comment_video_ids<'a>(&'a mut self, comment_ids: Vec<String>) -> GetCommentVideoIdsRequest<'a> {
+    pub fn get_comment_video_ids(&self, comment_ids: Vec<String>) -> GetCommentVideoIdsRequest<T> {
         GetCommentVideoIdsRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
-            comment_ids
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            comment_ids,
+            concurrency: batch::DEFAULT_CONCURRENCY,
         }
     }
 
-    pub fn get_comment_replies<'a>(&'a mut self, parent_id: String, page_token: Option<String>) -> GetCommentRepliesRequest<'a> {
+    pub fn get_comment_replies(&self, parent_id: String, page_token: Option<String>) -> GetCommentRepliesRequest<T> {
         GetCommentRepliesRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
             parent_id,
-            page_token
+            page_token,
+            text_format: TextFormat::default(),
         }
     }
 
-    pub fn list_videos<'a>(&'a mut self, video_ids: Vec<String>) -> ListVideosRequest<'a> {
+    /// Comments by id. Takes full API ids as well as the compact ids in `Comment`, with replies
+    /// given as `parent_comment_id.comment_id`.
+    pub fn list_comments(&self, comment_ids: Vec<String>) -> ListCommentsRequest<T> {
+        ListCommentsRequest {
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            comment_ids,
+            concurrency: batch::DEFAULT_CONCURRENCY,
+        }
+    }
+
+    pub fn list_videos(&self, video_ids: Vec<String>) -> ListVideosRequest<T> {
         ListVideosRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
-            video_ids
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            video_ids,
+            concurrency: batch::DEFAULT_CONCURRENCY,
         }
     }
 
-    pub fn list_channels<'a>(&'a mut self, channel_ids: Vec<String>) -> ListChannelsRequest<'a> {
+    pub fn list_channels(&self, channel_ids: Vec<String>) -> ListChannelsRequest<T> {
         ListChannelsRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
-            channel_ids
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            channel_ids,
+            concurrency: batch::DEFAULT_CONCURRENCY,
         }
     }
 
-    pub fn delete_channel_section<'a>(&'a mut self, id: String) -> DeleteChannelSectionRequest<'a> {
+    pub fn delete_channel_section(&self, id: String) -> DeleteChannelSectionRequest<T> {
         DeleteChannelSectionRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
             id
         }
     }
 
-    pub fn list_playlist_items<'a>(&'a mut self, playlist_id: String, page_token: Option<String>, max_results: Option<u32>) -> ListPlaylistItemsRequest<'a> {
+    pub fn create_channel_section(&self, author_channel_id: String, position: u32, channels: Vec<String>) -> CreateChannelSectionRequest<T> {
+        CreateChannelSectionRequest {
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
+            author_channel_id,
+            position,
+            channels
+        }
+    }
+
+    pub fn list_playlist_items(&self, playlist_id: String, page_token: Option<String>, max_results: Option<u32>) -> ListPlaylistItemsRequest<T> {
         ListPlaylistItemsRequest {
-            client: &mut self.client,
-            ip: &self.ip,
-            fields: GoogleAPIRequestFields{
-                bearer_token: None,
-                key: None,
-                referrer: None
-            },
+            client: self.clone(),
+            fields: GoogleAPIRequestFields::default(),
             playlist_id,
             page_token,
             max_results
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use hyper::StatusCode;
+use crate::quota::ApiMethod;
+
+/// What happened to one attempt at sending a request
+#[derive(Debug, Clone, Copy)]
+pub struct RequestEvent {
+    pub method: ApiMethod,
+    /// `None` if no response was received, e.g. on a connection error or timeout
+    pub status: Option<StatusCode>,
+    /// `YouTubeError::kind` of the error, if the attempt failed
+    pub error: Option<&'static str>,
+    pub latency: Duration,
+    /// Units the API charges for the attempt, which it does even if the attempt fails with an
+    /// error response. 0 if no response was received.
+    pub quota_units: u64,
+}
+
+/// Receives an event for every attempt that is sent, including retries.
+///
+/// Implement this to feed your own metrics system, or use `PrometheusMetrics`.
+pub trait MetricsRecorder: Send + Sync + 'static {
+    fn record_request(&self, event: &RequestEvent);
+}
+
+/// Upper bounds of the latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 10] = [0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// A `MetricsRecorder` that keeps counters and latency histograms in memory and renders them in
+/// the Prometheus text exposition format:
+///
+/// - `youtube_requests_total{endpoint, status, error}`
+/// - `youtube_request_duration_seconds{endpoint}` (histogram)
+/// - `youtube_quota_units_total{endpoint}`
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    requests: BTreeMap<(&'static str, String, &'static str), u64>,
+    latency: BTreeMap<&'static str, Histogram>,
+    quota_units: BTreeMap<&'static str, u64>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        PrometheusMetrics::default()
+    }
+
+    /// Number of attempts recorded for `method`, across all statuses and errors
+    pub fn requests(&self, method: ApiMethod) -> u64 {
+        self.state.lock().unwrap().requests
+            .iter()
+            .filter(|((endpoint, _, _), _)| *endpoint == method.name())
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Quota units spent on `method`
+    pub fn quota_units(&self, method: ApiMethod) -> u64 {
+        self.state.lock().unwrap().quota_units.get(method.name()).copied().unwrap_or(0)
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP youtube_requests_total Requests sent to the YouTube Data API.\n");
+        out.push_str("# TYPE youtube_requests_total counter\n");
+        for ((endpoint, status, error), count) in &state.requests {
+            writeln!(out, "youtube_requests_total{{endpoint=\"{}\",status=\"{}\",error=\"{}\"}} {}", endpoint, status, error, count).unwrap();
+        }
+
+        out.push_str("# HELP youtube_request_duration_seconds Time until the response was received.\n");
+        out.push_str("# TYPE youtube_request_duration_seconds histogram\n");
+        for (endpoint, histogram) in &state.latency {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                writeln!(out, "youtube_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}", endpoint, bound, cumulative).unwrap();
+            }
+            writeln!(out, "youtube_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}", endpoint, histogram.count).unwrap();
+            writeln!(out, "youtube_request_duration_seconds_sum{{endpoint=\"{}\"}} {}", endpoint, histogram.sum).unwrap();
+            writeln!(out, "youtube_request_duration_seconds_count{{endpoint=\"{}\"}} {}", endpoint, histogram.count).unwrap();
+        }
+
+        out.push_str("# HELP youtube_quota_units_total Quota units spent.\n");
+        out.push_str("# TYPE youtube_quota_units_total counter\n");
+        for (endpoint, units) in &state.quota_units {
+            writeln!(out, "youtube_quota_units_total{{endpoint=\"{}\"}} {}", endpoint, units).unwrap();
+        }
+
+        out
+    }
+}
+
+impl MetricsRecorder for PrometheusMetrics {
+    fn record_request(&self, event: &RequestEvent) {
+        let endpoint = event.method.name();
+        let status = event.status.map_or_else(|| "none".to_string(), |status| status.as_u16().to_string());
+        let error = event.error.unwrap_or("none");
+
+        let mut state = self.state.lock().unwrap();
+        *state.requests.entry((endpoint, status, error)).or_default() += 1;
+        *state.quota_units.entry(endpoint).or_default() += event.quota_units;
+
+        let seconds = event.latency.as_secs_f64();
+        let histogram = state.latency.entry(endpoint).or_default();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            histogram.buckets[i] += 1;
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+}
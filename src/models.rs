@@ -37,7 +37,7 @@ pub struct Video {
     pub made_for_kids: Option<bool>
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Comment {
     pub user_id: String,
     pub comment_id: String,
@@ -51,6 +51,14 @@ pub struct Comment {
     pub published_at: i64
 }
 
+/// The video a comment was posted on and the channel that wrote it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentOwner {
+    pub video_id: String,
+    /// Channel id without the `UC` prefix
+    pub author_user_id: String,
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct PlaylistItem {
     pub kind: String,
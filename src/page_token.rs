@@ -0,0 +1,114 @@
+//! Page tokens of offset-paginated endpoints are base64 encoded protobuf messages holding the
+//! offset of the first item on the page. Decoding them shows how far a walk has got, and
+//! synthesizing them lets workers start at arbitrary offsets:
+//!
+//! ```ignore
+//! // Worker `i` of 4 reads its quarter of a 10,000 item playlist
+//! let mut request = youtube_client.list_playlist_items(playlist_id, None, Some(50));
+//! request.set_page_token(PageToken::playlist_items(i * 2500).encode());
+//! let items: Vec<_> = request.stream_limit(2500).try_collect().await?;
+//! ```
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use prost::Message;
+
+/// Prefix of the nested cursor in playlistItems tokens
+const CURSOR_PREFIX: &str = "PT:";
+
+#[derive(Clone, PartialEq, Message)]
+struct RawPageToken {
+    #[prost(uint32, optional, tag = "1")]
+    offset: Option<u32>,
+    // Always 0 in the tokens the API returns
+    #[prost(uint32, optional, tag = "2")]
+    unknown: Option<u32>,
+    #[prost(string, optional, tag = "3")]
+    cursor: Option<String>,
+}
+
+/// A decoded page token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageToken {
+    /// `{1: offset, 2: 0}`, returned by subscriptions, e.g. `CAUQAA` for offset 5
+    Offset(u32),
+    /// `{2: 0, 3: "PT:" + base64({1: offset})}`, returned by playlistItems, e.g. `EAAaBlBUOkNESQ`
+    /// for offset 50
+    CursorOffset(u32),
+    /// Any other token, such as the continuation tokens of commentThreads, which hold no offset
+    /// and can't be synthesized
+    Opaque(String),
+}
+
+impl PageToken {
+    /// A token for subscriptions starting at `offset`
+    pub fn subscriptions(offset: u32) -> Self {
+        PageToken::Offset(offset)
+    }
+
+    /// A token for playlistItems starting at `offset`
+    pub fn playlist_items(offset: u32) -> Self {
+        PageToken::CursorOffset(offset)
+    }
+
+    /// Decodes a token. Tokens that don't have exactly one of the offset layouts are `Opaque`.
+    pub fn decode(token: &str) -> Self {
+        decode_offset(token).unwrap_or_else(|| PageToken::Opaque(token.to_string()))
+    }
+
+    pub fn encode(&self) -> String {
+        match self {
+            PageToken::Offset(offset) => encode_raw(&RawPageToken {
+                offset: Some(*offset),
+                unknown: Some(0),
+                cursor: None,
+            }),
+            PageToken::CursorOffset(offset) => {
+                let cursor = encode_raw(&RawPageToken {
+                    offset: Some(*offset),
+                    unknown: None,
+                    cursor: None,
+                });
+                encode_raw(&RawPageToken {
+                    offset: None,
+                    unknown: Some(0),
+                    cursor: Some(format!("{}{}", CURSOR_PREFIX, cursor)),
+                })
+            },
+            PageToken::Opaque(token) => token.clone(),
+        }
+    }
+
+    /// Offset of the first item on the page, if the token has one
+    pub fn offset(&self) -> Option<u32> {
+        match self {
+            PageToken::Offset(offset) | PageToken::CursorOffset(offset) => Some(*offset),
+            PageToken::Opaque(_) => None,
+        }
+    }
+}
+
+fn encode_raw(raw: &RawPageToken) -> String {
+    URL_SAFE_NO_PAD.encode(raw.encode_to_vec())
+}
+
+fn decode_raw(token: &str) -> Option<RawPageToken> {
+    let bytes = URL_SAFE_NO_PAD.decode(token.trim_end_matches('=')).ok()?;
+    RawPageToken::decode(bytes.as_slice()).ok()
+}
+
+fn decode_offset(token: &str) -> Option<PageToken> {
+    let page_token = match decode_raw(token)? {
+        RawPageToken { offset: Some(offset), unknown: Some(0), cursor: None } => PageToken::Offset(offset),
+        RawPageToken { offset: None, unknown: Some(0), cursor: Some(cursor) } => {
+            match decode_raw(cursor.strip_prefix(CURSOR_PREFIX)?)? {
+                RawPageToken { offset: Some(offset), unknown: None, cursor: None } => PageToken::CursorOffset(offset),
+                _ => return None,
+            }
+        },
+        _ => return None,
+    };
+
+    // Only accept tokens we'd encode the same way, so unknown fields aren't silently dropped
+    (page_token.encode() == token.trim_end_matches('=')).then_some(page_token)
+}
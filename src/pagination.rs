@@ -0,0 +1,163 @@
+use std::future::Future;
+use std::sync::Arc;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use crate::YouTubeError;
+use crate::checkpoint::{Checkpoint, CheckpointStore};
+
+/// A request for an endpoint that returns results in pages.
+///
+/// Besides sending a single page with `send()`, these requests can be turned into a stream that
+/// follows `next_page_token` until the last page:
+///
+/// ```ignore
+/// let items: Vec<PlaylistItem> = youtube_client
+///     .list_playlist_items(playlist_id, None, Some(50))
+///     .stream()
+///     .try_collect()
+///     .await?;
+/// ```
+///
+/// Pages are only fetched as the stream is polled, so dropping the stream or using `take` stops
+/// pagination early.
+pub trait PagedRequest: Clone + Send + Sized + 'static {
+    type Page: Send + 'static;
+    type Item: Send + 'static;
+
+    /// Sends the request for a single page
+    fn send_page(self) -> impl Future<Output = Result<Self::Page, YouTubeError>> + Send;
+
+    fn set_page_token(&mut self, page_token: String);
+
+    fn next_page_token(page: &Self::Page) -> Option<&str>;
+
+    fn items(page: &Self::Page) -> &[Self::Item];
+
+    fn into_items(page: Self::Page) -> Vec<Self::Item>;
+
+    /// Unix timestamp of an item, tracked in `Checkpoint::last_seen`
+    fn item_timestamp(_item: &Self::Item) -> Option<i64> {
+        None
+    }
+
+    /// Streams every page, starting at the request's page token. The stream ends after the first
+    /// error.
+    fn into_pages(self) -> BoxStream<'static, Result<Self::Page, YouTubeError>> {
+        stream::try_unfold(Some(self), |request| async move {
+            let Some(request) = request else {
+                return Ok(None);
+            };
+
+            let mut next_request = request.clone();
+            let page = request.send_page().await?;
+
+            let next_request = match Self::next_page_token(&page) {
+                Some(page_token) if !page_token.is_empty() => {
+                    next_request.set_page_token(page_token.to_string());
+                    Some(next_request)
+                },
+                _ => None,
+            };
+
+            Ok(Some((page, next_request)))
+        }).boxed()
+    }
+
+    /// Streams the items of every page
+    fn stream(self) -> BoxStream<'static, Result<Self::Item, YouTubeError>> {
+        self.into_pages()
+            .map_ok(|page| stream::iter(Self::into_items(page).into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Streams at most `limit` items, without fetching pages beyond the one holding the last item
+    fn stream_limit(self, limit: usize) -> BoxStream<'static, Result<Self::Item, YouTubeError>> {
+        self.stream().take(limit).boxed()
+    }
+
+    /// Like `into_pages`, but records progress under `key` in `store` and resumes from there.
+    ///
+    /// A page counts as done once the stream is polled again after yielding it, so after a crash
+    /// the page that was being processed is fetched again. Once the last page is done the
+    /// checkpoint is marked finished and the stream is empty on later runs; save a default
+    /// `Checkpoint` under `key` to start over.
+    fn into_pages_with_checkpoint<S: CheckpointStore>(self, store: Arc<S>, key: impl Into<String>) -> BoxStream<'static, Result<Self::Page, YouTubeError>> {
+        let state = CheckpointState {
+            request: Some(self),
+            checkpoint: None,
+            dirty: false,
+            store,
+            key: key.into(),
+        };
+
+        stream::try_unfold(state, |mut state| async move {
+            let mut checkpoint = match state.checkpoint.take() {
+                Some(checkpoint) => checkpoint,
+                None => {
+                    let checkpoint = state.store.load(&state.key).await?.unwrap_or_default();
+                    if checkpoint.finished {
+                        return Ok(None);
+                    }
+                    if let (Some(page_token), Some(request)) = (&checkpoint.page_token, &mut state.request) {
+                        request.set_page_token(page_token.clone());
+                    }
+                    checkpoint
+                },
+            };
+
+            if state.dirty {
+                state.store.save(&state.key, &checkpoint).await?;
+                state.dirty = false;
+            }
+
+            let Some(request) = state.request.take() else {
+                return Ok(None);
+            };
+
+            let mut next_request = request.clone();
+            let page = request.send_page().await?;
+
+            let items = Self::items(&page);
+            checkpoint.pages += 1;
+            checkpoint.items += items.len() as u64;
+            checkpoint.last_seen = items.iter()
+                .filter_map(Self::item_timestamp)
+                .chain(checkpoint.last_seen)
+                .max();
+
+            match Self::next_page_token(&page) {
+                Some(page_token) if !page_token.is_empty() => {
+                    checkpoint.page_token = Some(page_token.to_string());
+                    next_request.set_page_token(page_token.to_string());
+                    state.request = Some(next_request);
+                },
+                _ => {
+                    checkpoint.page_token = None;
+                    checkpoint.finished = true;
+                },
+            }
+
+            state.checkpoint = Some(checkpoint);
+            state.dirty = true;
+            Ok(Some((page, state)))
+        }).boxed()
+    }
+
+    /// Like `stream`, but resumable, see `into_pages_with_checkpoint`
+    fn stream_with_checkpoint<S: CheckpointStore>(self, store: Arc<S>, key: impl Into<String>) -> BoxStream<'static, Result<Self::Item, YouTubeError>> {
+        self.into_pages_with_checkpoint(store, key)
+            .map_ok(|page| stream::iter(Self::into_items(page).into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+}
+
+struct CheckpointState<R, S> {
+    request: Option<R>,
+    // Loaded when the stream is first polled
+    checkpoint: Option<Checkpoint>,
+    // Whether `checkpoint` has changed since it was last saved
+    dirty: bool,
+    store: Arc<S>,
+    key: String,
+}
@@ -1,28 +1,70 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::future::Future;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::pagination::PagedRequest;
+use crate::response::check_response;
+use http_body_util::Full;
+use hyper::{Method, Request};
+use chrono::DateTime;
 use serde::Deserialize;
 use crate::models::*;
 
-pub struct ListPlaylistItemsRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct ListPlaylistItemsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub playlist_id: String,
     pub page_token: Option<String>,
     pub max_results: Option<u32>,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for ListPlaylistItemsRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for ListPlaylistItemsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
+impl<T> Clone for ListPlaylistItemsRequest<T> {
+    fn clone(&self) -> Self {
+        ListPlaylistItemsRequest {
+            client: self.client.clone(),
+            fields: self.fields.clone(),
+            playlist_id: self.playlist_id.clone(),
+            page_token: self.page_token.clone(),
+            max_results: self.max_results,
+        }
+    }
+}
+
+impl<T: Transport> PagedRequest for ListPlaylistItemsRequest<T> {
+    type Page = ListPlaylistItemsResponse;
+    type Item = PlaylistItem;
+
+    fn send_page(self) -> impl Future<Output = Result<Self::Page, YouTubeError>> + Send {
+        self.send()
+    }
+
+    fn set_page_token(&mut self, page_token: String) {
+        self.page_token = Some(page_token);
+    }
+
+    fn next_page_token(page: &Self::Page) -> Option<&str> {
+        page.next_page_token.as_deref()
+    }
+
+    fn items(page: &Self::Page) -> &[Self::Item] {
+        &page.items
+    }
+
+    fn into_items(page: Self::Page) -> Vec<Self::Item> {
+        page.items
+    }
+
+    fn item_timestamp(item: &Self::Item) -> Option<i64> {
+        DateTime::parse_from_rfc3339(&item.snippet.published_at).ok().map(|dt| dt.timestamp())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
     kind: String,
@@ -55,11 +97,11 @@ pub struct ListPlaylistItemsResponse {
     pub items: Vec<PlaylistItem>,
 }
 
-impl<'a> ListPlaylistItemsRequest<'a> {
+impl<T: Transport> ListPlaylistItemsRequest<T> {
     pub async fn send(self) -> Result<ListPlaylistItemsResponse, YouTubeError> {
         let mut url = format!(
             "https://{}/youtube/v3/playlistItems?part=snippet%2Cid%2CcontentDetails&playlistId={}",
-            self.ip, self.playlist_id
+            self.client.ip, self.playlist_id
         );
 
         if let Some(page_token) = &self.page_token {
@@ -70,41 +112,30 @@ impl<'a> ListPlaylistItemsRequest<'a> {
             url.push_str(&format!("&maxResults={}", max_results));
         }
 
-        if let Some(key) = self.fields.key {
-            url.push_str(&format!("&key={}", key));
-        }
+        let body_bytes = self.client.execute(ApiMethod::PlaylistItemsList, &self.fields, |key| {
+            let mut req_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str());
 
-        let mut req_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url);
+            // Sent as a header rather than a query parameter so it doesn't end up in error URLs
+            if let Some(key) = key {
+                req_builder = req_builder.header("X-Goog-Api-Key", key);
+            }
 
-        if let Some(bearer_token) = self.fields.bearer_token {
-            req_builder = req_builder.header("Authorization", format!("Bearer {}", bearer_token));
-        }
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                req_builder = req_builder.header("Authorization", format!("Bearer {}", bearer_token));
+            }
 
-        if let Some(referrer) = self.fields.referrer {
-            req_builder = req_builder.header("Referer", referrer);
-        }
+            if let Some(referrer) = &self.fields.referrer {
+                req_builder = req_builder.header("Referer", referrer);
+            }
 
-        let req = req_builder
-            .body(Empty::<Bytes>::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let res = self.client.request(req).await?;
-        let status = res.status();
-
-        match status {
-            StatusCode::OK => {},
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => return Err(YouTubeError::Unauthorized),
-            StatusCode::FORBIDDEN => return Err(YouTubeError::Forbidden),
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::INTERNAL_SERVER_ERROR => return Err(YouTubeError::InternalServerError),
-            _ => return Err(YouTubeError::UnknownStatusCode(status)),
-        }
+            req_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
 
-        let body = res.collect().await?.to_bytes();
-        let body_str = std::str::from_utf8(&body)
+        let body_str = std::str::from_utf8(&body_bytes)
             .map_err(|e| YouTubeError::ParseError(e.to_string()))?;
 
         let api_response: ApiResponse = serde_json::from_str(body_str)?;
@@ -119,4 +150,4 @@ impl<'a> ListPlaylistItemsRequest<'a> {
             items: api_response.items,
         })
     }
-}
\ No newline at end of file
+}
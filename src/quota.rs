@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use crate::YouTubeError;
+
+/// The API methods used by this crate, with their quota cost.
+///
+/// See https://developers.google.com/youtube/v3/determine_quota_cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiMethod {
+    VideosList,
+    ChannelsList,
+    CommentThreadsList,
+    CommentsList,
+    SubscriptionsList,
+    PlaylistItemsList,
+    ChannelSectionsInsert,
+    ChannelSectionsDelete,
+}
+
+impl ApiMethod {
+    /// Quota units charged for a single call
+    pub fn quota_cost(self) -> u64 {
+        match self {
+            ApiMethod::VideosList
+            | ApiMethod::ChannelsList
+            | ApiMethod::CommentThreadsList
+            | ApiMethod::CommentsList
+            | ApiMethod::SubscriptionsList
+            | ApiMethod::PlaylistItemsList => 1,
+            ApiMethod::ChannelSectionsInsert
+            | ApiMethod::ChannelSectionsDelete => 50,
+        }
+    }
+
+    /// The API resource, e.g. `commentThreads`
+    pub fn resource(self) -> &'static str {
+        match self {
+            ApiMethod::VideosList => "videos",
+            ApiMethod::ChannelsList => "channels",
+            ApiMethod::CommentThreadsList => "commentThreads",
+            ApiMethod::CommentsList => "comments",
+            ApiMethod::SubscriptionsList => "subscriptions",
+            ApiMethod::PlaylistItemsList => "playlistItems",
+            ApiMethod::ChannelSectionsInsert | ApiMethod::ChannelSectionsDelete => "channelSections",
+        }
+    }
+
+    /// Whether sending the call twice has the same effect as sending it once. Calls that aren't
+    /// are only retried when the API clearly didn't act on them, see `RetryPolicy`.
+    pub fn is_idempotent(self) -> bool {
+        !matches!(self, ApiMethod::ChannelSectionsInsert)
+    }
+
+    /// The method name as used in the API reference, e.g. `commentThreads.list`
+    pub fn name(self) -> &'static str {
+        match self {
+            ApiMethod::VideosList => "videos.list",
+            ApiMethod::ChannelsList => "channels.list",
+            ApiMethod::CommentThreadsList => "commentThreads.list",
+            ApiMethod::CommentsList => "comments.list",
+            ApiMethod::SubscriptionsList => "subscriptions.list",
+            ApiMethod::PlaylistItemsList => "playlistItems.list",
+            ApiMethod::ChannelSectionsInsert => "channelSections.insert",
+            ApiMethod::ChannelSectionsDelete => "channelSections.delete",
+        }
+    }
+}
+
+/// Tracks quota units spent per API key.
+///
+/// Like the API itself, usage resets at midnight Pacific time. Every request that is sent is
+/// charged, including failed ones and retries. Requests authorized only by a bearer token are
+/// not tracked.
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    daily_budget: Option<u64>,
+    usage: Mutex<HashMap<String, (NaiveDate, u64)>>,
+}
+
+impl QuotaTracker {
+    pub fn new() -> Self {
+        QuotaTracker::default()
+    }
+
+    /// Refuses requests with `YouTubeError::QuotaBudgetExceeded` once a key would go over
+    /// `daily_budget` units for the day (10,000 is the default allocation for a project)
+    pub fn with_daily_budget(mut self, daily_budget: u64) -> Self {
+        self.daily_budget = Some(daily_budget);
+        self
+    }
+
+    pub fn daily_budget(&self) -> Option<u64> {
+        self.daily_budget
+    }
+
+    /// Units spent by `key` today
+    pub fn used(&self, key: &str) -> u64 {
+        let today = pacific_date(Utc::now());
+        let usage = self.usage.lock().unwrap();
+        match usage.get(key) {
+            Some((date, used)) if *date == today => *used,
+            _ => 0,
+        }
+    }
+
+    /// Units left for `key` today, if a budget is configured
+    pub fn remaining(&self, key: &str) -> Option<u64> {
+        self.daily_budget.map(|budget| budget.saturating_sub(self.used(key)))
+    }
+
+    /// Units spent today by every key that has been used
+    pub fn usage(&self) -> HashMap<String, u64> {
+        let today = pacific_date(Utc::now());
+        self.usage.lock().unwrap()
+            .iter()
+            .filter(|(_, (date, _))| *date == today)
+            .map(|(key, (_, used))| (key.clone(), *used))
+            .collect()
+    }
+
+    /// Charges `method` to `key`, or fails without charging if that would exceed the budget
+    pub(crate) fn charge(&self, key: &str, method: ApiMethod) -> Result<(), YouTubeError> {
+        let today = pacific_date(Utc::now());
+        let cost = method.quota_cost();
+
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage.entry(key.to_string()).or_insert((today, 0));
+        if entry.0 != today {
+            *entry = (today, 0);
+        }
+
+        if let Some(budget) = self.daily_budget {
+            if entry.1 + cost > budget {
+                return Err(YouTubeError::QuotaBudgetExceeded { used: entry.1, cost, budget });
+            }
+        }
+
+        entry.1 += cost;
+        Ok(())
+    }
+}
+
+/// Returns the current date in America/Los_Angeles, which is when the API quota resets.
+///
+/// US daylight saving time runs from 2am on the second Sunday of March to 2am on the first
+/// Sunday of November.
+pub(crate) fn pacific_date(now: DateTime<Utc>) -> NaiveDate {
+    let year = now.year();
+    let two_am = NaiveTime::from_hms_opt(2, 0, 0).unwrap();
+
+    // 2am PST is 10:00 UTC, 2am PDT is 09:00 UTC
+    let dst_start = nth_sunday(year, 3, 2).and_time(two_am) + Duration::hours(8);
+    let dst_end = nth_sunday(year, 11, 1).and_time(two_am) + Duration::hours(7);
+
+    let naive = now.naive_utc();
+    let offset = if naive >= dst_start && naive < dst_end { 7 } else { 8 };
+    (naive - Duration::hours(offset)).date()
+}
+
+fn nth_sunday(year: i32, month: u32, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n).unwrap()
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::quota::ApiMethod;
+
+/// Caps the rate of outgoing requests, globally and per API resource.
+///
+/// Requests over the limit wait for a permit instead of failing. Permits are handed out in the
+/// order requests ask for them. Every attempt needs a permit, including retries.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    global: Option<TokenBucket>,
+    resources: HashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    /// Limits all requests to `requests_per_second`, allowing bursts of up to `burst` requests.
+    ///
+    /// Panics if `requests_per_second` isn't positive.
+    pub fn with_global_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.global = Some(TokenBucket::new(requests_per_second, burst));
+        self
+    }
+
+    /// Limits requests to one resource, e.g. `with_resource_limit("commentThreads", 5.0, 10)`.
+    /// Requests must pass both the resource limit and the global limit.
+    pub fn with_resource_limit(mut self, resource: impl Into<String>, requests_per_second: f64, burst: u32) -> Self {
+        self.resources.insert(resource.into(), TokenBucket::new(requests_per_second, burst));
+        self
+    }
+
+    /// Waits until a request to `method` may be sent
+    pub(crate) async fn acquire(&self, method: ApiMethod) {
+        if let Some(bucket) = self.resources.get(method.resource()) {
+            bucket.acquire().await;
+        }
+
+        if let Some(bucket) = &self.global {
+            bucket.acquire().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    requests_per_second: f64,
+    burst: f64,
+    // Tokens go negative while requests are queued for a permit
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(requests_per_second > 0.0, "requests_per_second must be positive");
+        let burst = burst.max(1) as f64;
+        TokenBucket {
+            requests_per_second,
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let refill = now.duration_since(state.1).as_secs_f64() * self.requests_per_second;
+            state.0 = (state.0 + refill).min(self.burst) - 1.0;
+            state.1 = now;
+
+            if state.0 >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-state.0 / self.requests_per_second)
+        };
+
+        tokio::time::sleep(wait).await;
+    }
+}
@@ -0,0 +1,101 @@
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode};
+use serde::Deserialize;
+use crate::{ApiErrorDetails, YouTubeError};
+
+/// The error envelope returned by Google APIs, e.g.
+/// `{"error":{"code":403,"message":"...","errors":[{"domain":"youtube.quota","reason":"quotaExceeded"}]}}`
+#[derive(Debug, Deserialize)]
+struct ErrorEnvelope {
+    error: ApiError,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ApiError {
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub errors: Vec<ApiErrorDetail>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ApiErrorDetail {
+    #[serde(default)]
+    pub domain: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ApiError {
+    /// Whether any of the error details has one of `reasons`
+    pub fn has_reason(&self, reasons: &[&str]) -> bool {
+        self.errors.iter().any(|detail| reasons.contains(&detail.reason.as_str()))
+    }
+}
+
+/// Builds the error for an error response, e.g. `YouTubeError::NotFound`
+pub(crate) type ErrorConstructor = fn(Box<ApiErrorDetails>) -> YouTubeError;
+
+/// Returns the body of a successful response, or maps the status code and error envelope to a
+/// `YouTubeError`
+pub(crate) fn check_response(resp: Response<Bytes>) -> Result<Bytes, YouTubeError> {
+    check_response_with(resp, |_| None)
+}
+
+/// Like `check_response`, but gives `map_error` the first chance to pick the variant for an error
+/// envelope, for errors that only some endpoints return
+pub(crate) fn check_response_with(resp: Response<Bytes>, map_error: fn(&ApiError) -> Option<ErrorConstructor>) -> Result<Bytes, YouTubeError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp.into_body());
+    }
+
+    let body_bytes = resp.into_body();
+    let api_error = serde_json::from_slice::<ErrorEnvelope>(&body_bytes)
+        .map(|envelope| envelope.error)
+        .unwrap_or_default();
+
+    let error: ErrorConstructor = if let Some(error) = map_error(&api_error) {
+        error
+    } else if is_quota_exceeded(&api_error) {
+        |details| YouTubeError::QuotaExceeded(Some(details))
+    } else if api_error.has_reason(&["rateLimitExceeded", "userRateLimitExceeded"]) {
+        YouTubeError::Ratelimited
+    } else {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => YouTubeError::Ratelimited,
+            StatusCode::UNAUTHORIZED => YouTubeError::Unauthorized,
+            StatusCode::FORBIDDEN => YouTubeError::Forbidden,
+            StatusCode::NOT_FOUND => YouTubeError::NotFound,
+            status if status.is_server_error() => YouTubeError::InternalServerError,
+            _ => YouTubeError::UnknownStatusCode,
+        }
+    };
+
+    Err(error(Box::new(details(status, api_error, &body_bytes))))
+}
+
+fn details(status: StatusCode, api_error: ApiError, body_bytes: &Bytes) -> ApiErrorDetails {
+    let detail = api_error.errors.into_iter().next().unwrap_or_default();
+    let non_empty = |s: String| (!s.is_empty()).then_some(s);
+
+    let message = match api_error.message.is_empty() {
+        true => String::from_utf8_lossy(body_bytes).into_owned(),
+        false => api_error.message,
+    };
+
+    ApiErrorDetails {
+        status,
+        reason: non_empty(detail.reason),
+        domain: non_empty(detail.domain),
+        message,
+        url: None,
+    }
+}
+
+fn is_quota_exceeded(api_error: &ApiError) -> bool {
+    api_error.errors.iter().any(|detail| detail.domain == "youtube.quota")
+        || api_error.has_reason(&["quotaExceeded", "dailyLimitExceeded"])
+        // Older responses only carry the message
+        || api_error.message.starts_with("The request cannot be completed because you have exceeded your")
+}
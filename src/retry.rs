@@ -0,0 +1,128 @@
+use std::time::Duration;
+use chrono::DateTime;
+use hyper::HeaderMap;
+use hyper::header::RETRY_AFTER;
+use rand::Rng;
+use crate::YouTubeError;
+
+/// Controls how `send()` retries failed requests.
+///
+/// Delays grow exponentially from `base_delay` (doubling on each attempt) and are capped at
+/// `max_delay`. With jitter enabled, each delay is drawn uniformly from `0..=delay` so that many
+/// workers hitting the same error don't retry in lockstep.
+///
+/// Calls that aren't idempotent, like `channelSections.insert`, are not retried after a timeout,
+/// a broken connection or a server error, since the API may have applied them already. They are
+/// still retried when rate limited or when the connection couldn't be established.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    pub respect_retry_after: bool,
+    pub retryable: fn(&YouTubeError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// 3 attempts, 500ms base delay, 30s max delay, jitter on and `Retry-After` honored
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+            retryable: Self::default_retryable,
+        }
+    }
+
+    /// A policy that sends every request exactly once. This is what clients use by default.
+    pub fn disabled() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::new()
+        }
+    }
+
+    /// Errors for which `YouTubeError::is_retryable` is true are retried by default
+    pub fn default_retryable(error: &YouTubeError) -> bool {
+        error.is_retryable()
+    }
+
+    /// Total number of attempts including the first one
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Whether to wait for the duration in the `Retry-After` header when the server sends one
+    pub fn with_retry_after(mut self, respect_retry_after: bool) -> Self {
+        self.respect_retry_after = respect_retry_after;
+        self
+    }
+
+    /// Sets which errors are retried, e.g. `|e| matches!(e, YouTubeError::InternalServerError(_))`
+    pub fn with_retryable(mut self, retryable: fn(&YouTubeError) -> bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Returns how long to wait before the next attempt, or `None` if the error should be
+    /// returned to the caller. `attempt` is the number of attempts made so far.
+    pub(crate) fn next_delay(&self, attempt: u32, error: &YouTubeError, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts || !(self.retryable)(error) {
+            return None;
+        }
+
+        if let Some(retry_after) = retry_after.filter(|_| self.respect_retry_after) {
+            // Retrying earlier than the server asked for would just fail again
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let backoff = self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+
+        if self.jitter {
+            let millis = backoff.as_millis() as u64;
+            Some(Duration::from_millis(rand::thread_rng().gen_range(0..=millis)))
+        } else {
+            Some(backoff)
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let seconds = (date.timestamp() - chrono::Utc::now().timestamp()).max(0);
+    Some(Duration::from_secs(seconds as u64))
+}
@@ -1,27 +1,67 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::future::Future;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::pagination::PagedRequest;
+use crate::response::{check_response_with, ApiError, ErrorConstructor};
+use http_body_util::Full;
+use hyper::{Method, Request};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
-pub struct GetSubscriptionsRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct GetSubscriptionsRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub channel_id: String,
     pub page_token: Option<String>
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for GetSubscriptionsRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for GetSubscriptionsRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
 
+impl<T> Clone for GetSubscriptionsRequest<T> {
+    fn clone(&self) -> Self {
+        GetSubscriptionsRequest {
+            client: self.client.clone(),
+            fields: self.fields.clone(),
+            channel_id: self.channel_id.clone(),
+            page_token: self.page_token.clone(),
+        }
+    }
+}
+
+impl<T: Transport> PagedRequest for GetSubscriptionsRequest<T> {
+    type Page = GetSubscriptionsResponse;
+    type Item = Subscription;
+
+    fn send_page(self) -> impl Future<Output = Result<Self::Page, YouTubeError>> + Send {
+        self.send()
+    }
+
+    fn set_page_token(&mut self, page_token: String) {
+        self.page_token = Some(page_token);
+    }
+
+    fn next_page_token(page: &Self::Page) -> Option<&str> {
+        page.next_page_token.as_deref()
+    }
+
+    fn items(page: &Self::Page) -> &[Self::Item] {
+        &page.subscriptions
+    }
+
+    fn into_items(page: Self::Page) -> Vec<Self::Item> {
+        page.subscriptions
+    }
+
+    fn item_timestamp(item: &Self::Item) -> Option<i64> {
+        Some(item.timestamp)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
     #[serde(rename = "nextPageToken")]
@@ -60,110 +100,45 @@ pub struct GetSubscriptionsResponse {
     pub subscriptions: Vec<Subscription>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
-
-impl<'a> GetSubscriptionsRequest<'a> {
+impl<T: Transport> GetSubscriptionsRequest<T> {
 
     pub async fn send(self) -> Result<GetSubscriptionsResponse, YouTubeError> {
 
-        let mut url = format!("https://{}/youtube/v3/subscriptions?part=snippet&order=alphabetical&channelId={}&maxResults=50", self.ip, self.channel_id);
+        let mut url = format!("https://{}/youtube/v3/subscriptions?part=snippet&order=alphabetical&channelId={}&maxResults=50", self.client.ip, self.channel_id);
         
         // if there is a page_token
         if let Some(page_token) = self.page_token {
             url = format!("{}&pageToken={}", url, page_token);
         }
 
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header("X-Goog-Fieldmask", "nextPageToken,items.snippet(publishedAt,resourceId.channelId)");
-
-        // Only add the API key header if it's present
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
+        let body_bytes = self.client.execute(ApiMethod::SubscriptionsList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header("X-Goog-Fieldmask", "nextPageToken,items.snippet(publishedAt,resourceId.channelId)");
 
-        if let Some(referrer) = self.fields.referrer {
-            if !referrer.is_empty() {
-                request_builder = request_builder.header("Referer", referrer);
+            // Only add the API key header if it's present
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
             }
-        }
-
-        // Only add the Authorization header if a bearer is present
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
 
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                match serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    Ok(error_response) => {
-                        match error_response.error.message.as_str() {
-                            "Subscriptions could not be retrieved because the subscriber's account is closed." => {
-                                return Err(YouTubeError::AccountClosed)
-                            },
-                            "Subscriptions could not be retrieved because the subscriber's account is suspended." => {
-                                return Err(YouTubeError::AccountTerminated)
-                            },
-                            "The requester is not allowed to access the requested subscriptions." => {
-                                return Err(YouTubeError::SubscriptionsPrivate)
-                            },
-                            msg if msg.starts_with("The request cannot be completed because you have exceeded your") => {
-                                return Err(YouTubeError::Ratelimited)
-                            },
-                            _ => {
-                                eprintln!("Unknown forbidden error message: {}", error_response.error.message);
-                                return Err(YouTubeError::Forbidden)
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        let body_str = String::from_utf8_lossy(&body_bytes);
-                        eprintln!("Failed to parse error response: {}\nResponse body: {}", e, body_str);
-                        return Err(YouTubeError::Forbidden)
-                    }
+            if let Some(referrer) = &self.fields.referrer {
+                if !referrer.is_empty() {
+                    request_builder = request_builder.header("Referer", referrer);
                 }
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unauthorized error response: {}", body_str);
-                return Err(YouTubeError::Unauthorized);
-            },
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                return Err(YouTubeError::InternalServerError);
             }
-            StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError);
+
+            // Only add the Authorization header if a bearer is present
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
             }
-            StatusCode::OK => (), // Continue processing
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, |resp| check_response_with(resp, map_error)).await?;
+
         let api_response: ApiResponse = serde_json::from_slice(&body_bytes)?;
 
         // Transform the data into our desired format
@@ -182,4 +157,23 @@ impl<'a> GetSubscriptionsRequest<'a> {
     
     }
 
-}
\ No newline at end of file
+}
+
+fn map_error(error: &ApiError) -> Option<ErrorConstructor> {
+    if error.has_reason(&["subscriberAccountClosed", "accountClosed"])
+        || error.message == "Subscriptions could not be retrieved because the subscriber's account is closed." {
+        return Some(YouTubeError::AccountClosed);
+    }
+
+    if error.has_reason(&["subscriberAccountSuspended", "accountSuspended"])
+        || error.message == "Subscriptions could not be retrieved because the subscriber's account is suspended." {
+        return Some(YouTubeError::AccountTerminated);
+    }
+
+    if error.has_reason(&["subscriptionForbidden"])
+        || error.message == "The requester is not allowed to access the requested subscriptions." {
+        return Some(YouTubeError::SubscriptionsPrivate);
+    }
+
+    None
+}
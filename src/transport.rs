@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::future::Future;
+use std::time::Duration;
+use hyper::body::Bytes;
+use hyper::{Request, Response};
+use http_body_util::{BodyExt, Full};
+use tokio::time::error::Elapsed;
+use crate::{HttpClient, YouTubeError};
+use crate::connector::CONNECT_TIMEOUT;
+
+/// The HTTP layer used by `YouTubeDataV3Client` to talk to the API.
+///
+/// Implementations receive a fully built request (method, URI, `Host` and auth headers and an
+/// optional body already set) and return the response with its body collected into memory. This makes it possible to
+/// plug in a mock or recording transport in tests, or a custom connection pool in production.
+pub trait Transport: Send + Sync + 'static {
+    fn request(&self, req: Request<Full<Bytes>>) -> impl Future<Output = Result<Response<Bytes>, YouTubeError>> + Send;
+}
+
+/// Request extension holding the connect timeout of `YouTubeDataV3Client::with_connect_timeout`
+/// or `GoogleAPIRequest::with_connect_timeout`, for transports to apply to new connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectTimeout(pub Duration);
+
+/// Default transport backed by a hyper client, as returned by `initialize_client`.
+#[derive(Clone)]
+pub struct HyperTransport {
+    client: HttpClient,
+}
+
+impl HyperTransport {
+    pub fn new(client: HttpClient) -> Self {
+        HyperTransport { client }
+    }
+}
+
+impl From<HttpClient> for HyperTransport {
+    fn from(client: HttpClient) -> Self {
+        HyperTransport::new(client)
+    }
+}
+
+impl Transport for HyperTransport {
+    async fn request(&self, req: Request<Full<Bytes>>) -> Result<Response<Bytes>, YouTubeError> {
+        // The connector is called from within this future, where it picks up the timeout
+        let resp = match req.extensions().get::<ConnectTimeout>().copied() {
+            Some(ConnectTimeout(connect_timeout)) => CONNECT_TIMEOUT.scope(connect_timeout, self.client.request(req)).await,
+            None => self.client.request(req).await,
+        };
+        let resp = resp.map_err(|e| {
+            if is_timeout(&e) {
+                YouTubeError::Timeout
+            } else {
+                YouTubeError::from(e)
+            }
+        })?;
+        let (parts, body) = resp.into_parts();
+        let body_bytes = body.collect().await?.to_bytes();
+        Ok(Response::from_parts(parts, body_bytes))
+    }
+}
+
+/// Whether the connector gave up because of its connect timeout
+fn is_timeout(error: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if error.is::<Elapsed>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
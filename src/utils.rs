@@ -12,6 +12,10 @@ pub enum ClientError {
     TlsError(#[from] native_tls::Error),
 }
 
+/// Picks a random address from `subnet` (a /32 to /64 IPv6 prefix).
+///
+/// `range_id` is placed in the 16 bits directly after the prefix, so different range ids never
+/// share addresses. The remaining bits are random.
 pub fn get_rand_ipv6(subnet: &str, range_id: u16) -> Result<IpAddr, Box<dyn std::error::Error>> {
     // Split the subnet string into address and prefix length
     let parts: Vec<&str> = subnet.split('/').collect();
@@ -23,23 +27,25 @@ pub fn get_rand_ipv6(subnet: &str, range_id: u16) -> Result<IpAddr, Box<dyn std:
     let ipv6: u128 = parts[0].parse::<Ipv6Addr>()?.into();
 
     // Parse the prefix length
-    let prefix_len: u8 = parts[1].parse()?;
-    if prefix_len != 48 {
-        return Err("Only /48 subnets are supported".into());
+    let prefix_len: u32 = parts[1].parse()?;
+    if !(32..=64).contains(&prefix_len) {
+        return Err("Only /32 to /64 subnets are supported".into());
     }
 
-    // Clear the lower 80 bits (128 - 48) of the network address
-    let net_part = (ipv6 >> 80) << 80;
+    // Clear the host bits of the network address
+    let host_bits = 128 - prefix_len;
+    let net_part = (ipv6 >> host_bits) << host_bits;
     
-    // Shift the range_id into position (place it in bits 64-79)
-    let range_part = (range_id as u128) << 64;
+    // Shift the range_id into the 16 bits right after the prefix
+    let random_bits = host_bits - 16;
+    let range_part = (range_id as u128) << random_bits;
     
-    // Generate random number for host portion (lower 64 bits)
+    // Generate random number for the remaining host portion
     let rand: u128 = rand::thread_rng().gen();
-    let host_part = rand & ((1u128 << 64) - 1);  // Only keep lower 64 bits
+    let host_part = rand & ((1u128 << random_bits) - 1);
     
     // Combine all parts
     let result = net_part | range_part | host_part;
 
     Ok(IpAddr::V6(result.into()))
-}
\ No newline at end of file
+}
@@ -1,23 +1,26 @@
-use crate::{GoogleAPIRequestFields, YouTubeError};
-use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector;
-use hyper::body::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::{Method, Request, StatusCode};
+use std::collections::HashMap;
+use crate::{GoogleAPIRequestFields, YouTubeDataV3Client, YouTubeError};
+use crate::transport::{HyperTransport, Transport};
+use crate::quota::ApiMethod;
+use crate::response::check_response;
+use crate::batch::send_chunked;
+use http_body_util::Full;
+use hyper::{Method, Request};
 use chrono::DateTime;
 use serde::Deserialize;
+use crate::ids::compact_channel_id;
 use crate::models::*;
 
-pub struct ListVideosRequest<'a> {
-    pub client: &'a mut Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
-    pub ip: &'a str,
-    pub fields: GoogleAPIRequestFields<'a>,
+pub struct ListVideosRequest<T = HyperTransport> {
+    pub client: YouTubeDataV3Client<T>,
+    pub fields: GoogleAPIRequestFields,
     pub video_ids: Vec<String>,
+    /// Chunks of 50 ids sent at the same time
+    pub concurrency: usize,
 }
 
-impl<'a> AsMut<GoogleAPIRequestFields<'a>> for ListVideosRequest<'a> {
-    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields<'a> {
+impl<T> AsMut<GoogleAPIRequestFields> for ListVideosRequest<T> {
+    fn as_mut(&mut self) -> &mut GoogleAPIRequestFields {
         &mut self.fields
     }
 }
@@ -70,87 +73,29 @@ struct VideoStatistics {
     comment_count: Option<String>
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Error,
-}
-
-#[derive(Debug, Deserialize)]
-struct Error {
-    message: String,
-}
+impl<T: Transport> ListVideosRequest<T> {
+    /// Sets how many chunks of 50 ids are requested at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
 
-impl<'a> ListVideosRequest<'a> {
+    /// Fetches the videos in chunks of 50 ids. The result has one entry per requested id, in the
+    /// same order, with a placeholder for videos that weren't found.
     pub async fn send(self) -> Result<Vec<Video>, YouTubeError> {
         // Preallocate the result vector with the known size
         let mut videos = Vec::with_capacity(self.video_ids.len());
-        
-        // Build query string
-        let ids_str = self.video_ids.join(",");
-
-        let url = format!(
-            "https://{}/youtube/v3/videos?part=status,snippet,id,statistics&id={}", 
-            self.ip, 
-            ids_str
-        );
-
-        let mut request_builder = Request::builder()
-            .method(Method::GET)
-            .uri(url)
-            .header("Host", "youtube.googleapis.com")
-            .header("X-Goog-Fieldmask", "items(id,snippet(publishedAt,channelId,title,description,tags,defaultLanguage,defaultAudioLanguage),status(uploadStatus,privacyStatus,madeForKids),statistics(viewCount,likeCount,commentCount))");
-
-        if let Some(key) = self.fields.key {
-            request_builder = request_builder.header("X-Goog-Api-Key", key);
-        }
-
-        if let Some(bearer_token) = self.fields.bearer_token {
-            request_builder = request_builder.header("Authorization", bearer_token);
-        }
-
-        if let Some(referrer) = self.fields.referrer {
-            request_builder = request_builder.header("Referer", referrer);
-        }
 
-        let req = request_builder
-            .body(Empty::new())
-            .map_err(|e| YouTubeError::Other(Box::new(e)))?;
-
-        let resp = self.client.request(req).await?;
-
-        match resp.status() {
-            StatusCode::OK => (),
-            StatusCode::TOO_MANY_REQUESTS => return Err(YouTubeError::Ratelimited),
-            StatusCode::FORBIDDEN => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                if let Ok(error_response) = serde_json::from_slice::<ErrorResponse>(&body_bytes) {
-                    if error_response.error.message.starts_with("The request cannot be completed because you have exceeded your") {
-                        return Err(YouTubeError::Ratelimited);
-                    }
-                }
-                return Err(YouTubeError::Forbidden);
-            },
-            StatusCode::NOT_FOUND => return Err(YouTubeError::NotFound),
-            StatusCode::UNAUTHORIZED => return Err(YouTubeError::Unauthorized),
-            StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
-                return Err(YouTubeError::InternalServerError)
-            },
-            status => {
-                let body_bytes = resp.into_body().collect().await?.to_bytes();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                eprintln!("Unknown status code {}: {}", status.as_u16(), body_str);
-                return Err(YouTubeError::UnknownStatusCode(status));
-            },
-        };
-
-        let body_bytes = resp.into_body().collect().await?.to_bytes();
-        let api_response: ApiResponse = serde_json::from_slice(&body_bytes)?;
+        let responses = send_chunked(&self.video_ids, self.concurrency, |ids_str| self.send_chunk(ids_str)).await?;
+        let api_videos: HashMap<String, ApiVideo> = responses
+            .into_iter()
+            .flat_map(|api_response| api_response.items.unwrap_or_default())
+            .map(|api_video| (api_video.id.clone(), api_video))
+            .collect();
 
         // Process each requested video ID
         for video_id in self.video_ids {
-            let api_video = api_response.items
-                .as_ref()
-                .and_then(|items| items.iter().find(|v| v.id == video_id));
+            let api_video = api_videos.get(&video_id);
 
             let video = match api_video {
                 None => Video {
@@ -185,7 +130,7 @@ impl<'a> ListVideosRequest<'a> {
                     let user_id = api_video.snippet
                         .as_ref()
                         .and_then(|s| s.channel_id.as_ref())
-                        .map(|id| id.strip_prefix("UC").unwrap_or(id).to_string());
+                        .map(|id| compact_channel_id(id).to_string());
 
                     let created_at = api_video.snippet
                         .as_ref()
@@ -227,4 +172,38 @@ impl<'a> ListVideosRequest<'a> {
 
         Ok(videos)
     }
-}
\ No newline at end of file
+
+    async fn send_chunk(&self, ids_str: String) -> Result<ApiResponse, YouTubeError> {
+        let url = format!(
+            "https://{}/youtube/v3/videos?part=status,snippet,id,statistics&id={}",
+            self.client.ip,
+            ids_str
+        );
+
+        let body_bytes = self.client.execute(ApiMethod::VideosList, &self.fields, |key| {
+            let mut request_builder = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("Host", "youtube.googleapis.com")
+                .header("X-Goog-Fieldmask", "items(id,snippet(publishedAt,channelId,title,description,tags,defaultLanguage,defaultAudioLanguage),status(uploadStatus,privacyStatus,madeForKids),statistics(viewCount,likeCount,commentCount))");
+
+            if let Some(key) = key {
+                request_builder = request_builder.header("X-Goog-Api-Key", key);
+            }
+
+            if let Some(bearer_token) = &self.fields.bearer_token {
+                request_builder = request_builder.header("Authorization", bearer_token);
+            }
+
+            if let Some(referrer) = &self.fields.referrer {
+                request_builder = request_builder.header("Referer", referrer);
+            }
+
+            request_builder
+                .body(Full::default())
+                .map_err(|e| YouTubeError::Other(Box::new(e)))
+        }, check_response).await?;
+
+        Ok(serde_json::from_slice(&body_bytes)?)
+    }
+}